@@ -6,13 +6,16 @@
 pub mod menu;
 
 use std::env;
-use std::path::Path;
+use std::time::Duration;
 
 use menu::create_menu;
-use tauri::Manager;
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+use tokio::sync::broadcast::{self, error::RecvError};
 
 use macrograph::api::{Request, Response};
 use macrograph::core::{Core, CoreController};
+use macrograph::discovery::default_package_dirs;
 
 #[tauri::command]
 async fn core_request(
@@ -22,30 +25,49 @@ async fn core_request(
   Ok(core_controller.send(req).await)
 }
 
+// Emits everything sent on the channel as window events
+fn forward<T: Serialize + Clone + Send + 'static>(
+  handle: AppHandle,
+  event: &'static str,
+  mut receiver: broadcast::Receiver<T>,
+) {
+  tokio::spawn(async move {
+    loop {
+      match receiver.recv().await {
+        Ok(payload) => {
+          handle.emit_all(event, payload).ok();
+        }
+        Err(RecvError::Lagged(_)) => continue,
+        Err(RecvError::Closed) => break,
+      }
+    }
+  });
+}
+
 #[tokio::main]
 async fn main() {
+  simple_logger::init_with_level(log::Level::Info).unwrap();
+
   let mut core = Core::new();
 
-  let lib_extension = match env::consts::OS {
-    "macos" => "dylib",
-    "windows" => "dll",
-    _ => "so",
-  };
-
-  for package in ["logic", "utils", "keyboard", "obs"] {
-    let mut current_exe_path = env::current_exe().unwrap();
-    current_exe_path.pop();
-    let path = Path::new(&current_exe_path)
-      .join(format!("libmg_pkg_{}.{}", package, lib_extension))
-      .canonicalize()
-      .unwrap();
-
-    core.load_library(&path.to_str().unwrap());
+  for failure in core.load_package_dirs(&default_package_dirs()) {
+    log::warn!(
+      "Failed to load package at {}: {}",
+      failure.path.display(),
+      failure.error
+    );
   }
 
   core.setup();
 
+  // Reload packages as they're rebuilt, for package development
+  if env::var_os("MACROGRAPH_WATCH_PACKAGES").is_some() {
+    core.watch_packages(Some(Duration::from_secs(1)));
+  }
+
   let controller = core.get_controller();
+  let notifications = controller.subscribe();
+  let execution_trace = controller.subscribe_execution_trace();
 
   tokio::spawn(async move {
     core.start().await;
@@ -54,6 +76,13 @@ async fn main() {
   tauri::Builder::default()
     .menu(create_menu())
     .manage(controller)
+    .setup(|app| {
+      forward(app.handle(), "core-notification", notifications);
+      // Trace events are only sent once the frontend has sent SubscribeExecutionTrace
+      forward(app.handle(), "execution-trace", execution_trace);
+
+      Ok(())
+    })
     .invoke_handler(tauri::generate_handler![core_request])
     .run(tauri::generate_context!())
     .expect("error while running tauri application");
@@ -28,7 +28,7 @@ export const ConnectionRender = observer(() => {
       )
         if (UI.draggingPin.type.variant === "primitive") {
           colourClass = DataColourClasses[UI.draggingPin.type.value];
-        } else {
+        } else if (UI.draggingPin.type.variant === "list") {
           colourClass = DataColourClasses[UI.draggingPin.type.value.value];
         }
 
@@ -66,7 +66,7 @@ export const ConnectionRender = observer(() => {
               let colourClass;
               if (i.type.variant === "primitive") {
                 colourClass = DataColourClasses[i.type.value];
-              } else {
+              } else if (i.type.variant === "list") {
                 colourClass = DataColourClasses[i.type.value.value];
               }
 
@@ -28,24 +28,29 @@ const DataPinTypeColours: Record<
   },
 };
 
+// Wildcards take the colour of their type once a connection resolves them
+const WildcardPinColours = {
+  active: "border-white bg-white",
+  base: "border-white hover:bg-white",
+};
+
 interface Props {
   pin: DataInput | DataOutput;
 }
 
 export const DataPin: FC<Props> = ({ pin }) => {
-  let type: PrimitiveType;
+  let colourClass = WildcardPinColours;
   
   let isArray = false;
   if (pin.type.variant === "primitive") {
-    type = pin.type.value;
-  } else {
+    colourClass = DataPinTypeColours[pin.type.value];
+  } else if (pin.type.variant === "list") {
     isArray = true;
-    if (pin.type.value.variant === "primitive") type = pin.type.value.value;
+    if (pin.type.value.variant === "primitive")
+      colourClass = DataPinTypeColours[pin.type.value.value];
     else throw "";
   }
 
-  let colourClass = DataPinTypeColours[type];
-
   const { ref, active } = usePin(pin);
 
   return (
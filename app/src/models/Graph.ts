@@ -64,13 +64,20 @@ export class Graph {
     if (_send) {
       if (!pinsCanConnect(output, input)) return;
 
-      await send("ConnectIO", {
-        graph: this.id,
-        output_node: output.node.id,
-        output: output.name,
-        input_node: input.node.id,
-        input: input.name,
-      });
+      // The core rejects connections that would create cycles or that it
+      // can't type, in which case the pins are left as they were
+      try {
+        await send("ConnectIO", {
+          graph: this.id,
+          output_node: output.node.id,
+          output: output.name,
+          input_node: input.node.id,
+          input: input.name,
+        });
+      } catch (e) {
+        console.error(e);
+        return;
+      }
     }
 
     runInAction(() => {
@@ -22,5 +22,7 @@ export const send = async <T extends RequestType>(
     },
   });
 
+  if (res.type === "Error") throw new Error(res.data.message);
+
   return res.data;
 };
@@ -6,6 +6,9 @@ export function pinsCanConnect(
   input: DataInput | ExecInput
 ) {
   if (output instanceof DataOutput && input instanceof DataInput) {
+    // The core knows which types a wildcard can still be resolved to
+    if (output.type.variant === "wildcard" || input.type.variant === "wildcard")
+      return true;
     if (output.type.variant === input.type.variant) {
       if (output.type.variant === "primitive") {
         return output.type.value === input.type.value;
@@ -0,0 +1,166 @@
+use std::{env, path::PathBuf, process, time::Duration};
+
+use macrograph::{
+    core::Core,
+    discovery::{default_package_dirs, PackageDir},
+    notification::CoreNotification,
+    project::LoadProjectReport,
+};
+use tokio::sync::broadcast::error::RecvError;
+
+const USAGE: &str = "Usage: macrograph-cli <validate|run> <project> [package dir...]";
+
+// How long engines and executions are given to stop after SIGINT
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
+
+enum Command {
+    Validate,
+    Run,
+}
+
+#[tokio::main]
+async fn main() {
+    simple_logger::init_with_level(log::Level::Info).unwrap();
+
+    let mut args = env::args().skip(1);
+
+    let command = match args.next().as_deref() {
+        Some("validate") => Command::Validate,
+        Some("run") => Command::Run,
+        _ => exit_with_usage(),
+    };
+    let project = args
+        .next()
+        .map(PathBuf::from)
+        .unwrap_or_else(|| exit_with_usage());
+
+    // Package dirs passed as arguments replace the default ones
+    let mut package_dirs: Vec<PackageDir> = args
+        .map(|arg| PackageDir::Packages(PathBuf::from(arg)))
+        .collect();
+    if package_dirs.is_empty() {
+        package_dirs = default_package_dirs();
+    }
+
+    let mut core = Core::new();
+
+    let package_failures = core.load_package_dirs(&package_dirs);
+
+    for failure in &package_failures {
+        eprintln!(
+            "Failed to load package at {}: {}",
+            failure.path.display(),
+            failure.error
+        );
+    }
+
+    let report = match core.load_project(&project).await {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("Failed to load project {}: {}", project.display(), e);
+            core.shutdown(SHUTDOWN_TIMEOUT).await;
+            process::exit(1);
+        }
+    };
+
+    // Validating also fails if any package failed to load, even one the
+    // project doesn't use
+    let valid = print_report(&report) && package_failures.is_empty();
+
+    match command {
+        Command::Validate => {
+            core.shutdown(SHUTDOWN_TIMEOUT).await;
+
+            if valid {
+                println!("Project is valid");
+            } else {
+                process::exit(1);
+            }
+        }
+        Command::Run => run(core).await,
+    }
+}
+
+async fn run(mut core: Core) {
+    let mut failed = false;
+    let mut notifications = core.get_controller().subscribe();
+
+    tokio::spawn(async move {
+        loop {
+            match notifications.recv().await {
+                Ok(notification) => log_notification(notification),
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => break,
+            }
+        }
+    });
+
+    core.setup();
+
+    tokio::select! {
+        _ = core.start() => {}
+        res = tokio::signal::ctrl_c() => {
+            if let Err(e) = res {
+                eprintln!("Failed to listen for SIGINT: {}", e);
+                failed = true;
+            }
+        }
+    }
+
+    println!("Shutting down");
+
+    core.shutdown(SHUTDOWN_TIMEOUT).await;
+
+    if failed {
+        process::exit(1);
+    }
+}
+
+// Returns whether the project loaded without any missing nodes or connections
+fn print_report(report: &LoadProjectReport) -> bool {
+    for node in &report.missing_nodes {
+        eprintln!(
+            "Graph {}: node {} uses schema {} from package {}, which isn't loaded",
+            node.graph, node.node, node.schema, node.package
+        );
+    }
+
+    for connection in &report.failed_connections {
+        eprintln!(
+            "Connection from {} on node {} to {} on node {} is invalid",
+            connection.output, connection.output_node, connection.input, connection.input_node
+        );
+    }
+
+    report.missing_nodes.is_empty() && report.failed_connections.is_empty()
+}
+
+fn log_notification(notification: CoreNotification) {
+    match notification {
+        CoreNotification::EngineStatusChanged { package, status } => {
+            println!("Engine for package {} is now {:?}", package, status)
+        }
+        CoreNotification::EventFired { package, event } => {
+            println!("Event {} fired by package {}", event, package)
+        }
+        CoreNotification::ExecutionStarted {
+            execution,
+            graph,
+            node,
+        } => println!(
+            "Execution {} started from node {} in graph {}",
+            execution, node, graph
+        ),
+        CoreNotification::ExecutionFinished {
+            execution,
+            error: None,
+        } => println!("Execution {} finished", execution),
+        // Errors are already logged by the Core
+        _ => {}
+    }
+}
+
+fn exit_with_usage() -> ! {
+    eprintln!("{}", USAGE);
+    process::exit(1);
+}
@@ -1,4 +1,5 @@
 import type { ListType } from "./ListType";
 import type { PrimitiveType } from "./PrimitiveType";
+import type { WildcardType } from "./WildcardType";
 
-export type ValueType = { variant: "primitive", value: PrimitiveType } | { variant: "list", value: ListType };
\ No newline at end of file
+export type ValueType = { variant: "primitive", value: PrimitiveType } | { variant: "list", value: ListType } | { variant: "wildcard", value: WildcardType };
\ No newline at end of file
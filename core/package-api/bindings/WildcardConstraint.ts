@@ -0,0 +1,2 @@
+
+export type WildcardConstraint = "any" | "primitive" | "number" | "list";
\ No newline at end of file
@@ -0,0 +1,3 @@
+import type { WildcardConstraint } from "./WildcardConstraint";
+
+export interface WildcardType { group: number, constraint: WildcardConstraint, }
\ No newline at end of file
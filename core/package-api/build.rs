@@ -0,0 +1,20 @@
+use std::{env, process::Command};
+
+// Packages are loaded as Rust dylibs, which are only ABI compatible when built
+// with the same compiler, so the compiler version is embedded for the Core to check
+fn main() {
+    let rustc = env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+
+    let version = Command::new(rustc)
+        .arg("--version")
+        .output()
+        .ok()
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .unwrap_or_default();
+
+    println!(
+        "cargo:rustc-env=MACROGRAPH_RUSTC_VERSION={}",
+        version.trim()
+    );
+    println!("cargo:rerun-if-env-changed=RUSTC");
+}
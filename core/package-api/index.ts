@@ -5,3 +5,5 @@ export * from "./bindings/Primitive";
 export * from "./bindings/PrimitiveType";
 export * from "./bindings/Value";
 export * from "./bindings/ValueType";
+export * from "./bindings/WildcardConstraint";
+export * from "./bindings/WildcardType";
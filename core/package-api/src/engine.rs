@@ -1,11 +1,19 @@
-use std::any::Any;
+use std::{any::Any, future::Future};
 
-use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
+use futures::future;
+use tokio::sync::{
+    mpsc::{UnboundedReceiver, UnboundedSender},
+    watch,
+};
 
 use crate::EngineRequest;
 
 pub type InitialEngineState = Option<Box<dyn Any + Send + Sync>>;
 
+// Creates the engine's initial state. Called every time the engine is started,
+// since a crashed engine takes its state with it.
+pub type StateFn = fn() -> Box<dyn Any + Send + Sync>;
+
 #[derive(Debug)]
 pub struct Event {
     pub package: String,
@@ -31,6 +39,8 @@ pub struct EngineContext {
     pub request_receiver: UnboundedReceiver<EngineRequest>,
     pub package: String,
     pub handle: tokio::runtime::Handle,
+    // Set to true when the Core wants the engine to stop, eg. before reloading its package
+    pub stop_receiver: watch::Receiver<bool>,
 }
 
 impl EngineContext {
@@ -43,17 +53,45 @@ impl EngineContext {
 
     pub fn send(&self, event: &str, data: impl Any + Send + Sync) {
         self.event_sender
-            .send(Event::new(&self.package, event, data));
+            .send(Event::new(&self.package, event, data))
+            .ok();
     }
 
     pub async fn receive_request(&mut self) -> Option<EngineRequest> {
         self.request_receiver.recv().await
     }
+
+    pub fn stop_requested(&self) -> bool {
+        *self.stop_receiver.borrow()
+    }
+}
+
+async fn wait_for_stop(mut stop_receiver: watch::Receiver<bool>) {
+    while !*stop_receiver.borrow() {
+        if stop_receiver.changed().await.is_err() {
+            return;
+        }
+    }
+}
+
+// Runs an async engine on the package's runtime until it either finishes
+// or the Core requests that it stops
+pub fn block_on_engine<F, Fut>(ctx: EngineContext, run: F)
+where
+    F: FnOnce(EngineContext) -> Fut,
+    Fut: Future<Output = ()>,
+{
+    let handle = ctx.handle.clone();
+    let stop = wait_for_stop(ctx.stop_receiver.clone());
+
+    handle.block_on(async move {
+        future::select(Box::pin(run(ctx)), Box::pin(stop)).await;
+    });
 }
 
 pub struct EngineConfig {
     pub run: RunFn,
-    pub state: InitialEngineState,
+    pub state: Option<StateFn>,
 }
 
 // it's possible that this could make IO and timer operations unusable
@@ -61,10 +99,7 @@ pub struct EngineConfig {
 #[macro_export]
 macro_rules! run_fn {
     ($name:ident) => {
-        |mut ctx| {
-            let handle = ctx.handle.clone();
-            handle.block_on($name(ctx))
-        };
+        |ctx| $crate::engine::block_on_engine(ctx, $name)
     };
 }
 
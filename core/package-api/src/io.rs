@@ -3,7 +3,10 @@ use std::collections::HashMap;
 use crate::{
     list::List,
     primitive::Primitive,
-    value::types::{IntoType, ListType, PrimitiveType, ValueType},
+    property::{Property, PropertyKind},
+    value::types::{
+        IntoType, ListType, PrimitiveType, ValueType, WildcardConstraint, WildcardType,
+    },
     Value,
 };
 
@@ -82,16 +85,68 @@ pub enum OutputSchema {
 pub struct BuildSchema {
     pub inputs: Vec<InputSchema>,
     pub outputs: Vec<OutputSchema>,
+    pub properties: Vec<Property>,
+    // The node's property values, which may no longer be valid
+    values: HashMap<String, Primitive>,
 }
 
 impl BuildSchema {
     pub fn new() -> Self {
+        Self::with_properties(HashMap::new())
+    }
+
+    pub fn with_properties(values: HashMap<String, Primitive>) -> Self {
         Self {
             inputs: Vec::new(),
             outputs: Vec::new(),
+            properties: Vec::new(),
+            values,
         }
     }
 
+    // Declares a property, returning the node's value for it or the default
+    // if the node doesn't have a valid one
+    pub fn property(&mut self, name: &str, kind: PropertyKind, default: Primitive) -> Primitive {
+        let value = self
+            .values
+            .get(name)
+            .filter(|value| kind.accepts(value))
+            .cloned()
+            .unwrap_or(default);
+
+        self.properties.push(Property {
+            name: name.into(),
+            kind,
+            value: value.clone(),
+        });
+
+        value
+    }
+
+    // Defaults to the first option
+    pub fn choice_property(&mut self, name: &str, options: &[&str]) -> String {
+        let default = options.first().copied().unwrap_or_default();
+        let kind = PropertyKind::Choice {
+            options: options.iter().map(|option| option.to_string()).collect(),
+        };
+
+        self.property(name, kind, default.into())
+            .as_string()
+            .unwrap_or_default()
+    }
+
+    pub fn int_property(&mut self, name: &str, default: i32, min: i32, max: i32) -> i32 {
+        self.property(name, PropertyKind::Int { min, max }, default.into())
+            .as_int()
+            .unwrap_or(default)
+    }
+
+    pub fn string_property(&mut self, name: &str, default: &str) -> String {
+        self.property(name, PropertyKind::String, default.into())
+            .as_string()
+            .unwrap_or_default()
+    }
+
     #[inline]
     pub fn exec_input(&mut self, name: &str) {
         self.inputs.push(InputSchema::Exec(name.into()));
@@ -132,6 +187,11 @@ impl BuildSchema {
         self.data_input(name, ListType::Primitive(T::into_type()).into());
     }
 
+    #[inline]
+    pub fn wildcard_input(&mut self, name: &str, group: u32, constraint: WildcardConstraint) {
+        self.data_input(name, WildcardType { group, constraint }.into());
+    }
+
     #[inline]
     pub fn data_output(&mut self, name: &str, r#type: ValueType) {
         self.outputs.push(OutputSchema::Data(name.into(), r#type));
@@ -161,4 +221,15 @@ impl BuildSchema {
     pub fn list_output<T: IntoType<PrimitiveType>>(&mut self, name: &str) {
         self.data_output(name, ListType::Primitive(T::into_type()).into());
     }
+
+    #[inline]
+    pub fn wildcard_output(&mut self, name: &str, group: u32, constraint: WildcardConstraint) {
+        self.data_output(name, WildcardType { group, constraint }.into());
+    }
+}
+
+impl Default for BuildSchema {
+    fn default() -> Self {
+        Self::new()
+    }
 }
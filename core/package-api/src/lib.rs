@@ -4,6 +4,8 @@ pub mod io;
 pub mod package;
 pub mod types;
 pub mod engine;
+pub mod state;
+pub mod property;
 
 pub use value::*;
 pub use schema::*;
@@ -1,9 +1,45 @@
+use std::os::raw::c_char;
+
 use crate::{
     engine::EngineConfig,
-    types::{BuildFn, FireFn},
+    types::{BuildFn, FireFn, FlowFn},
     ExecuteFn, NodeSchema,
 };
 
+// Increment whenever a change is made to the package API that breaks
+// compatibility with previously built packages
+pub const API_VERSION: u32 = 7;
+
+// Exported by packages under the MACROGRAPH_PACKAGE_API_VERSION symbol so that
+// the Core can verify compatibility before calling into the package.
+// api_version must remain the first field so it can always be read.
+#[repr(C)]
+pub struct PackageDescriptor {
+    pub api_version: u32,
+    pub rustc_version: *const c_char,
+}
+
+unsafe impl Sync for PackageDescriptor {}
+
+impl PackageDescriptor {
+    pub const fn current() -> Self {
+        Self {
+            api_version: API_VERSION,
+            rustc_version: concat!(env!("MACROGRAPH_RUSTC_VERSION"), "\0").as_ptr()
+                as *const c_char,
+        }
+    }
+}
+
+#[macro_export]
+macro_rules! package_descriptor {
+    () => {
+        #[no_mangle]
+        pub static MACROGRAPH_PACKAGE_API_VERSION: $crate::package::PackageDescriptor =
+            $crate::package::PackageDescriptor::current();
+    };
+}
+
 pub struct Package {
     pub name: String,
     pub schemas: Vec<NodeSchema>,
@@ -45,7 +81,15 @@ impl Package {
         self.add_schema(NodeSchema::new_exec(name, build, execute));
     }
 
+    pub fn add_pure_schema(&mut self, name: &str, build: BuildFn, execute: ExecuteFn<()>) {
+        self.add_schema(NodeSchema::new_pure(name, build, execute));
+    }
+
     pub fn add_base_schema(&mut self, name: &str, build: BuildFn, execute: ExecuteFn) {
         self.add_schema(NodeSchema::new_base(name, build, execute));
     }
+
+    pub fn add_flow_schema(&mut self, name: &str, build: BuildFn, execute: FlowFn) {
+        self.add_schema(NodeSchema::new_flow(name, build, execute));
+    }
 }
@@ -0,0 +1,35 @@
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::primitive::Primitive;
+
+#[derive(TS, Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[ts(export)]
+#[serde(tag = "type", content = "data")]
+pub enum PropertyKind {
+    // One of the options, stored as a string
+    Choice { options: Vec<String> },
+    Int { min: i32, max: i32 },
+    String,
+}
+
+impl PropertyKind {
+    pub fn accepts(&self, value: &Primitive) -> bool {
+        match (self, value) {
+            (Self::Choice { options }, Primitive::String(value)) => options.contains(value),
+            (Self::Int { min, max }, Primitive::Int(value)) => (*min..=*max).contains(value),
+            (Self::String, Primitive::String(_)) => true,
+            _ => false,
+        }
+    }
+}
+
+// A setting of a node that its schema builds its IO from, declared by the
+// schema's build function
+#[derive(TS, Serialize, Deserialize, Debug, Clone)]
+#[ts(export)]
+pub struct Property {
+    pub name: String,
+    pub kind: PropertyKind,
+    pub value: Primitive,
+}
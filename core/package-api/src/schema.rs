@@ -1,5 +1,6 @@
 use std::{any::Any, ops::Deref};
 
+use serde::{de::DeserializeOwned, Serialize};
 use tokio::{
     runtime::{EnterGuard, Handle},
     sync::{
@@ -8,7 +9,10 @@ use tokio::{
     },
 };
 
-use crate::types::{AsyncExecuteFn, BuildFn, FireFn, SyncExecuteFn};
+use crate::{
+    state::{NodeState, SharedNodeState, StateGuard},
+    types::{AsyncExecuteFn, BuildFn, FireFn, FlowFn, SyncExecuteFn},
+};
 
 pub enum ExecuteFn<T = Option<&'static str>> {
     Sync(SyncExecuteFn<T>),
@@ -20,6 +24,47 @@ pub enum NodeSchemaType {
     Exec { execute: ExecuteFn<()> },
     Pure { execute: ExecuteFn<()> },
     Event { fire: FireFn },
+    // Can run multiple exec outputs in order, and loop
+    Flow { execute: FlowFn },
+}
+
+// What a Flow node does each time it's called
+pub enum FlowStep {
+    // Runs everything connected to the output, then calls the node again
+    Branch(&'static str),
+    // Continues the execution from the output without returning to the node
+    Then(&'static str),
+    // Stops this branch of the execution
+    Done,
+}
+
+pub struct FlowContext<'a> {
+    // The exec input the node was entered through
+    pub input: &'a str,
+    // How many times the node has been called since it was entered
+    pub iteration: usize,
+    state: &'a mut NodeState,
+}
+
+impl<'a> FlowContext<'a> {
+    pub fn new(input: &'a str, iteration: usize, state: &'a mut NodeState) -> Self {
+        Self {
+            input,
+            iteration,
+            state,
+        }
+    }
+
+    // State kept by the node between executions, see NodeState::get
+    pub fn state<T: Any + Send + Default>(&mut self) -> &mut T {
+        self.state.get()
+    }
+
+    pub fn saved_state<T: Any + Send + Default + Serialize + DeserializeOwned>(
+        &mut self,
+    ) -> &mut T {
+        self.state.get_saved()
+    }
 }
 
 pub struct NodeSchema {
@@ -56,6 +101,15 @@ impl NodeSchema {
         }
     }
 
+    pub fn new_pure(name: &str, build: BuildFn, execute: ExecuteFn<()>) -> Self {
+        Self {
+            name: name.into(),
+            build,
+            package: String::new(),
+            inner: NodeSchemaType::Pure { execute },
+        }
+    }
+
     pub fn new_event(name: &str, build: BuildFn, fire: FireFn) -> Self {
         Self {
             name: name.into(),
@@ -64,6 +118,15 @@ impl NodeSchema {
             inner: NodeSchemaType::Event { fire },
         }
     }
+
+    pub fn new_flow(name: &str, build: BuildFn, execute: FlowFn) -> Self {
+        Self {
+            name: name.into(),
+            build,
+            package: String::new(),
+            inner: NodeSchemaType::Flow { execute },
+        }
+    }
 }
 
 pub type EngineRequestData = Box<dyn Any + Send>;
@@ -77,11 +140,37 @@ pub enum EngineRequest {
 pub struct ExecuteContext {
     sender: Option<UnboundedSender<EngineRequest>>,
     handle: Handle,
+    state: SharedNodeState,
 }
 
 impl ExecuteContext {
-    pub fn new(sender: Option<UnboundedSender<EngineRequest>>, handle: Handle) -> Self {
-        Self { sender, handle }
+    pub fn new(
+        sender: Option<UnboundedSender<EngineRequest>>,
+        handle: Handle,
+        state: SharedNodeState,
+    ) -> Self {
+        Self {
+            sender,
+            handle,
+            state,
+        }
+    }
+
+    // State kept by the node between executions, see NodeState::get. The
+    // node's state is locked until the guard is dropped, so it shouldn't be
+    // held across awaits.
+    pub fn state<T: Any + Send + Default>(&self) -> StateGuard<'_, T> {
+        let mut state = self.state.lock().unwrap();
+        state.get::<T>();
+        StateGuard::new(state)
+    }
+
+    pub fn saved_state<T: Any + Send + Default + Serialize + DeserializeOwned>(
+        &self,
+    ) -> StateGuard<'_, T> {
+        let mut state = self.state.lock().unwrap();
+        state.get_saved::<T>();
+        StateGuard::new(state)
     }
 
     pub fn enter_handle(&self) -> EnterGuard<'_> {
@@ -90,7 +179,8 @@ impl ExecuteContext {
 
     pub fn send<T: Any + Send>(&self, data: T) {
         if let Some(sender) = &self.sender {
-            sender.send(EngineRequest::Send(Box::new(data))).unwrap();
+            // The engine may have crashed, in which case the request is dropped
+            sender.send(EngineRequest::Send(Box::new(data))).ok();
         }
     }
 
@@ -99,7 +189,7 @@ impl ExecuteContext {
             let (tx, rx) = oneshot::channel();
             sender
                 .send(EngineRequest::Invoke(Box::new(data), tx))
-                .unwrap();
+                .ok()?;
             let ret = rx.await.ok();
             ret.and_then(|data| data.downcast::<Option<T>>().ok()).and_then(|v| *v)
         } else {
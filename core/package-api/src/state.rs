@@ -0,0 +1,198 @@
+use std::{
+    any::Any,
+    marker::PhantomData,
+    ops::{Deref, DerefMut},
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use serde::{de::DeserializeOwned, Serialize};
+
+type SerializeFn = fn(&(dyn Any + Send)) -> Option<String>;
+
+// State kept by a node between executions. Each node has its own, which is
+// cleared when the node's graph is reset.
+#[derive(Default)]
+pub struct NodeState {
+    value: Option<Box<dyn Any + Send>>,
+    // Set for saved state, which is kept in the project as JSON
+    serialize: Option<SerializeFn>,
+    // Saved state loaded from a project, which can't be deserialized until
+    // the node asks for it with a type
+    loaded: Option<String>,
+}
+
+pub type SharedNodeState = Arc<Mutex<NodeState>>;
+
+impl NodeState {
+    // Starts out as T::default(), and is reset if a different type was stored
+    pub fn get<T: Any + Send + Default>(&mut self) -> &mut T {
+        if !self.value.as_ref().is_some_and(|value| (**value).is::<T>()) {
+            *self = Self {
+                value: Some(Box::new(T::default())),
+                ..Default::default()
+            };
+        }
+
+        self.value_mut()
+    }
+
+    // Like get, but the state is also saved in the project
+    pub fn get_saved<T: Any + Send + Default + Serialize + DeserializeOwned>(&mut self) -> &mut T {
+        if !self.value.as_ref().is_some_and(|value| (**value).is::<T>()) {
+            let value: T = self
+                .loaded
+                .take()
+                .and_then(|loaded| serde_json::from_str(&loaded).ok())
+                .unwrap_or_default();
+
+            self.value = Some(Box::new(value));
+        }
+
+        self.serialize = Some(|value| {
+            value
+                .downcast_ref::<T>()
+                .and_then(|value| serde_json::to_string(value).ok())
+        });
+
+        self.value_mut()
+    }
+
+    fn value_mut<T: Any>(&mut self) -> &mut T {
+        self.value
+            .as_mut()
+            .and_then(|value| (**value).downcast_mut())
+            .unwrap()
+    }
+
+    // The state to keep in the project, if it's saved state
+    pub fn save(&self) -> Option<String> {
+        match (&self.value, self.serialize) {
+            (Some(value), Some(serialize)) => serialize(value.as_ref()),
+            _ => self.loaded.clone(),
+        }
+    }
+
+    pub fn load(&mut self, saved: String) {
+        self.clear();
+        self.loaded = Some(saved);
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    // Drops everything created by the package's code, which has to happen
+    // before its library is unloaded. Saved state is kept as JSON and loaded
+    // again by the next get_saved.
+    pub fn detach(&mut self) {
+        match self.save() {
+            Some(saved) => self.load(saved),
+            None => self.clear(),
+        }
+    }
+}
+
+// A node's state of type T, locked until this is dropped
+pub struct StateGuard<'a, T> {
+    guard: MutexGuard<'a, NodeState>,
+    r#type: PhantomData<T>,
+}
+
+impl<'a, T: Any> StateGuard<'a, T> {
+    // The state must already hold a T
+    pub(crate) fn new(guard: MutexGuard<'a, NodeState>) -> Self {
+        Self {
+            guard,
+            r#type: PhantomData,
+        }
+    }
+}
+
+impl<'a, T: Any> Deref for StateGuard<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        self.guard
+            .value
+            .as_ref()
+            .and_then(|value| (**value).downcast_ref())
+            .unwrap()
+    }
+}
+
+impl<'a, T: Any> DerefMut for StateGuard<'a, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.guard.value_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn get_starts_at_default() {
+        let mut state = NodeState::default();
+
+        assert_eq!(*state.get::<i32>(), 0);
+
+        *state.get::<i32>() = 5;
+        assert_eq!(*state.get::<i32>(), 5);
+    }
+
+    #[test]
+    fn get_resets_other_types() {
+        let mut state = NodeState::default();
+        *state.get::<i32>() = 5;
+
+        assert_eq!(state.get::<String>(), "");
+        assert_eq!(*state.get::<i32>(), 0);
+    }
+
+    #[test]
+    fn only_saved_state_is_saved() {
+        let mut state = NodeState::default();
+
+        *state.get::<i32>() = 5;
+        assert_eq!(state.save(), None);
+
+        *state.get_saved::<i32>() = 5;
+        assert_eq!(state.save(), Some("5".into()));
+    }
+
+    #[test]
+    fn loaded_state_is_read_by_get_saved() {
+        let mut state = NodeState::default();
+        state.load("[1,2]".into());
+
+        // Kept as is until something asks for it
+        assert_eq!(state.save(), Some("[1,2]".into()));
+        assert_eq!(*state.get_saved::<Vec<i32>>(), vec![1, 2]);
+    }
+
+    #[test]
+    fn invalid_loaded_state_is_replaced_by_default() {
+        let mut state = NodeState::default();
+        state.load("not json".into());
+
+        assert_eq!(*state.get_saved::<i32>(), 0);
+        assert_eq!(state.save(), Some("0".into()));
+    }
+
+    #[test]
+    fn detach_keeps_only_saved_state() {
+        let mut state = NodeState::default();
+        *state.get_saved::<i32>() = 5;
+        state.detach();
+
+        assert!(state.value.is_none());
+        assert_eq!(*state.get_saved::<i32>(), 5);
+
+        let mut state = NodeState::default();
+        *state.get::<i32>() = 5;
+        state.detach();
+
+        assert!(state.value.is_none());
+        assert_eq!(state.save(), None);
+    }
+}
@@ -8,7 +8,7 @@ use ts_rs::TS;
 
 use super::{
     primitive::Primitive,
-    types::{FromType, IntoType, ListType, PrimitiveType},
+    types::{IntoType, ListType, PrimitiveType},
 };
 
 #[derive(TS, Serialize, Deserialize, Debug, Clone)]
@@ -3,7 +3,7 @@ use ts_rs::TS;
 
 use self::{
     primitive::Primitive,
-    types::{IntoType, ListType, PrimitiveType, ValueType},
+    types::{IntoType, PrimitiveType, ValueType},
 };
 
 pub mod list;
@@ -89,6 +89,9 @@ impl From<ValueType> for Value {
         match value {
             ValueType::Primitive(primitive) => Self::Primitive(primitive.into()),
             ValueType::List(list) => Self::List(list.into()),
+            // Nothing can be connected to an unresolved wildcard, so its
+            // value is never used
+            ValueType::Wildcard(_) => Self::Primitive(Primitive::Bool(false)),
         }
     }
 }
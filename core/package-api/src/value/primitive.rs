@@ -3,7 +3,7 @@ use ts_rs::TS;
 
 use super::types::PrimitiveType;
 
-#[derive(TS, Serialize, Deserialize, Debug, Clone)]
+#[derive(TS, Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[ts(export)]
 #[serde(rename_all = "camelCase", tag = "type", content = "value")]
 pub enum Primitive {
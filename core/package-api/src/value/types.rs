@@ -84,6 +84,13 @@ impl From<PrimitiveType> for ListType {
 pub enum ValueType {
     Primitive(PrimitiveType),
     List(ListType),
+    Wildcard(WildcardType),
+}
+
+impl ValueType {
+    pub fn is_wildcard(&self) -> bool {
+        matches!(self, Self::Wildcard(_))
+    }
 }
 
 impl From<PrimitiveType> for ValueType {
@@ -97,3 +104,53 @@ impl From<ListType> for ValueType {
         ValueType::List(list_type)
     }
 }
+
+impl From<WildcardType> for ValueType {
+    fn from(wildcard_type: WildcardType) -> Self {
+        ValueType::Wildcard(wildcard_type)
+    }
+}
+
+#[derive(TS, Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[ts(export)]
+#[serde(rename_all = "camelCase")]
+pub enum WildcardConstraint {
+    Any,
+    Primitive,
+    // Int or Float
+    Number,
+    List,
+}
+
+impl WildcardConstraint {
+    pub fn allows(&self, r#type: &ValueType) -> bool {
+        match (self, r#type) {
+            (_, ValueType::Wildcard(_)) => false,
+            (Self::Any, _) => true,
+            (Self::Primitive, ValueType::Primitive(_)) => true,
+            (Self::Number, ValueType::Primitive(PrimitiveType::Int | PrimitiveType::Float)) => true,
+            (Self::List, ValueType::List(_)) => true,
+            _ => false,
+        }
+    }
+
+    // Whether some type is allowed by both constraints, so that wildcards
+    // with them can be connected before either is resolved
+    pub fn overlaps(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Any, _) | (_, Self::Any) => true,
+            (Self::List, Self::List) => true,
+            (Self::List, _) | (_, Self::List) => false,
+            _ => true,
+        }
+    }
+}
+
+// A type that's decided by the first connection made to the IO. A node's
+// wildcards in the same group always resolve to the same type.
+#[derive(TS, Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[ts(export)]
+pub struct WildcardType {
+    pub group: u32,
+    pub constraint: WildcardConstraint,
+}
@@ -1,17 +1,18 @@
 use serde::Serialize;
 use ts_rs::TS;
 
-use crate::graph::Graph;
+use crate::{function::FunctionSignature, graph::Graph};
 
 use super::node::RawNode;
 
-#[derive(TS, Serialize, Debug)]
+#[derive(TS, Serialize, Debug, Clone)]
 #[ts(export)]
 #[serde(rename = "Graph")]
 pub struct RawGraph {
     pub id: i32,
     pub name: String,
     pub nodes: Vec<RawNode>,
+    pub function: Option<FunctionSignature>,
 }
 
 impl From<&Graph> for RawGraph {
@@ -20,6 +21,7 @@ impl From<&Graph> for RawGraph {
             id: graph.id,
             name: graph.name.clone(),
             nodes: graph.nodes.values().map(|n| n.as_ref().into()).collect(),
+            function: graph.function.clone(),
         }
     }
 }
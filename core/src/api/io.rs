@@ -4,14 +4,14 @@ use ts_rs::TS;
 
 use crate::io::{Input, Output};
 
-#[derive(TS, Serialize, Debug)]
+#[derive(TS, Serialize, Debug, Clone)]
 #[ts(export)]
 pub struct Connection {
     node: i32,
     io: String,
 }
 
-#[derive(TS, Serialize, Debug)]
+#[derive(TS, Serialize, Debug, Clone)]
 #[ts(export)]
 #[serde(tag = "variant", rename = "Input")]
 pub enum RawInput {
@@ -32,7 +32,7 @@ impl From<&Input> for RawInput {
         match input {
             Input::Data(input) => RawInput::Data {
                 name: input.name.clone(),
-                r#type: input.r#type.clone(),
+                r#type: input.r#type,
                 default_value: input.default_value.load().as_ref().clone(),
                 connection: input
                     .connected_output
@@ -60,7 +60,7 @@ impl From<&Input> for RawInput {
     }
 }
 
-#[derive(TS, Serialize, Debug)]
+#[derive(TS, Serialize, Debug, Clone)]
 #[ts(export)]
 #[serde(tag = "variant", rename = "Output")]
 pub enum RawOutput {
@@ -1,13 +1,23 @@
 use serde::{Deserialize, Serialize};
 use ts_rs::TS;
 
-use crate::node::Position;
-use macrograph_package_api::{Value, primitive::Primitive};
+use crate::{
+    custom_event::CustomEvent,
+    debugger::PausedExecution,
+    error::CoreError,
+    execution::{ConcurrencyPolicy, ExecutionId},
+    function::{FunctionParameter, FunctionSignature},
+    node::Position,
+    project::{LoadProjectReport, SerializedFragment},
+    variable::{Variable, VariableId, VariableScope},
+};
+use macrograph_package_api::{primitive::Primitive, Value};
 
 use self::{
     graph::RawGraph,
     io::{RawInput, RawOutput},
-    package::RawPackage,
+    node::RawNode,
+    package::{RawEngine, RawPackage},
 };
 
 pub mod graph;
@@ -32,6 +42,13 @@ pub enum Request {
         input: String,
         value: Primitive,
     },
+    // Rebuilds the node's IO, dropping connections to IO that it no longer has
+    SetNodeProperty {
+        graph: i32,
+        node: i32,
+        property: String,
+        value: Primitive,
+    },
     SetNodePosition {
         graph: i32,
         node: i32,
@@ -61,9 +78,138 @@ pub enum Request {
     },
     GetPackages,
     GetProject,
+    SaveProject {
+        path: String,
+    },
+    LoadProject {
+        path: String,
+    },
     Reset {
         graph: i32,
     },
+    GetEngineStatus,
+    RestartEngine {
+        package: String,
+    },
+    ReloadPackage {
+        package: String,
+    },
+    SetEventPolicy {
+        package: String,
+        event: String,
+        policy: ConcurrencyPolicy,
+    },
+    // Starts or stops sending execution trace events to
+    // CoreController::subscribe_execution_trace. Events are sent while any
+    // subscription remains, so every subscribe needs a matching unsubscribe.
+    SubscribeExecutionTrace {
+        subscribe: bool,
+    },
+    SetBreakpoint {
+        graph: i32,
+        node: i32,
+        enabled: bool,
+    },
+    GetPausedExecutions,
+    Continue {
+        execution: ExecutionId,
+    },
+    StepOver {
+        execution: ExecutionId,
+    },
+    InspectValue {
+        graph: i32,
+        node: i32,
+        io: String,
+        is_input: bool,
+    },
+    // Cancels all running executions if execution is None
+    CancelExecution {
+        execution: Option<ExecutionId>,
+    },
+    // Reverts or reapplies the graph's most recent change, sending the same
+    // notifications as the requests that made the change
+    Undo {
+        graph: i32,
+    },
+    Redo {
+        graph: i32,
+    },
+    // Makes the graph callable from other graphs, or not if function is None
+    SetGraphFunction {
+        graph: i32,
+        function: Option<FunctionSignature>,
+    },
+    CopyNodes {
+        graph: i32,
+        nodes: Vec<i32>,
+    },
+    // Pastes can target any graph, not just the one the nodes were copied from
+    PasteNodes {
+        graph: i32,
+        fragment: SerializedFragment,
+        offset: Position,
+    },
+    // The variable's type is the type of value, which is also its initial value
+    CreateVariable {
+        name: String,
+        scope: VariableScope,
+        value: Primitive,
+    },
+    DeleteVariable {
+        variable: VariableId,
+    },
+    SetVariableValue {
+        variable: VariableId,
+        value: Primitive,
+    },
+    // Creates or updates the custom event with the given name, or deletes it
+    // if payload is None
+    SetCustomEvent {
+        name: String,
+        payload: Option<Vec<FunctionParameter>>,
+    },
+}
+
+impl Request {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::CreateNode { .. } => "CreateNode",
+            Self::SetDefaultValue { .. } => "SetDefaultValue",
+            Self::SetNodeProperty { .. } => "SetNodeProperty",
+            Self::SetNodePosition { .. } => "SetNodePosition",
+            Self::ConnectIO { .. } => "ConnectIO",
+            Self::DisconnectIO { .. } => "DisconnectIO",
+            Self::DeleteNode { .. } => "DeleteNode",
+            Self::CreateGraph => "CreateGraph",
+            Self::RenameGraph { .. } => "RenameGraph",
+            Self::GetPackages => "GetPackages",
+            Self::GetProject => "GetProject",
+            Self::SaveProject { .. } => "SaveProject",
+            Self::LoadProject { .. } => "LoadProject",
+            Self::Reset { .. } => "Reset",
+            Self::GetEngineStatus => "GetEngineStatus",
+            Self::RestartEngine { .. } => "RestartEngine",
+            Self::ReloadPackage { .. } => "ReloadPackage",
+            Self::SetEventPolicy { .. } => "SetEventPolicy",
+            Self::SubscribeExecutionTrace { .. } => "SubscribeExecutionTrace",
+            Self::SetBreakpoint { .. } => "SetBreakpoint",
+            Self::GetPausedExecutions => "GetPausedExecutions",
+            Self::Continue { .. } => "Continue",
+            Self::StepOver { .. } => "StepOver",
+            Self::InspectValue { .. } => "InspectValue",
+            Self::CancelExecution { .. } => "CancelExecution",
+            Self::Undo { .. } => "Undo",
+            Self::Redo { .. } => "Redo",
+            Self::SetGraphFunction { .. } => "SetGraphFunction",
+            Self::CopyNodes { .. } => "CopyNodes",
+            Self::PasteNodes { .. } => "PasteNodes",
+            Self::CreateVariable { .. } => "CreateVariable",
+            Self::DeleteVariable { .. } => "DeleteVariable",
+            Self::SetVariableValue { .. } => "SetVariableValue",
+            Self::SetCustomEvent { .. } => "SetCustomEvent",
+        }
+    }
 }
 
 #[derive(TS, Serialize, Debug)]
@@ -76,6 +222,7 @@ pub enum Response {
         outputs: Vec<RawOutput>,
     },
     SetDefaultValue,
+    SetNodeProperty,
     SetNodePosition,
     ConnectIO,
     DisconnectIO,
@@ -90,6 +237,52 @@ pub enum Response {
     },
     GetProject {
         graphs: Vec<RawGraph>,
+        variables: Vec<Variable>,
+        custom_events: Vec<CustomEvent>,
+    },
+    SaveProject,
+    LoadProject {
+        graphs: Vec<RawGraph>,
+        report: LoadProjectReport,
     },
     Reset,
+    GetEngineStatus {
+        engines: Vec<RawEngine>,
+    },
+    RestartEngine,
+    ReloadPackage {
+        graphs: Vec<RawGraph>,
+    },
+    SetEventPolicy,
+    SubscribeExecutionTrace,
+    SetBreakpoint,
+    GetPausedExecutions {
+        executions: Vec<PausedExecution>,
+    },
+    Continue,
+    StepOver,
+    InspectValue {
+        value: Value,
+    },
+    CancelExecution,
+    Undo,
+    Redo,
+    SetGraphFunction,
+    CopyNodes {
+        fragment: SerializedFragment,
+    },
+    PasteNodes {
+        nodes: Vec<RawNode>,
+    },
+    CreateVariable {
+        variable: Variable,
+    },
+    DeleteVariable,
+    SetVariableValue,
+    SetCustomEvent,
+    Error {
+        request: String,
+        kind: CoreError,
+        message: String,
+    },
 }
@@ -1,3 +1,4 @@
+use macrograph_package_api::property::Property;
 use serde::Serialize;
 use ts_rs::TS;
 
@@ -8,7 +9,7 @@ use super::{
     schema::RawNodeSchemaRef,
 };
 
-#[derive(TS, Serialize, Debug)]
+#[derive(TS, Serialize, Debug, Clone)]
 #[ts(export)]
 #[serde(rename = "Node")]
 pub struct RawNode {
@@ -18,16 +19,20 @@ pub struct RawNode {
     pub position: Position,
     pub inputs: Vec<RawInput>,
     pub outputs: Vec<RawOutput>,
+    pub properties: Vec<Property>,
+    pub breakpoint: bool,
 }
 
 impl From<&Node> for RawNode {
     fn from(node: &Node) -> Self {
         Self {
             id: node.id,
-            schema: node.schema.as_ref().into(),
+            schema: node.schema().as_ref().into(),
             position: node.position.lock().unwrap().clone(),
             inputs: node.inputs.lock().unwrap().iter().map(|io| io.into()).collect(),
             outputs: node.outputs.lock().unwrap().iter().map(|io| io.into()).collect(),
+            properties: node.properties.lock().unwrap().clone(),
+            breakpoint: node.has_breakpoint(),
         }
     }
 }
@@ -1,7 +1,7 @@
 use serde::Serialize;
 use ts_rs::TS;
 
-use crate::package::Package;
+use crate::{discovery::PackageManifest, engine::EngineStatus, package::Package};
 
 use super::schema::RawNodeSchema;
 
@@ -11,6 +11,7 @@ use super::schema::RawNodeSchema;
 pub struct RawPackage {
     pub name: String,
     pub schemas: Vec<RawNodeSchema>,
+    pub manifest: Option<PackageManifest>,
 }
 
 impl From<&Package> for RawPackage {
@@ -18,6 +19,24 @@ impl From<&Package> for RawPackage {
         Self {
             name: package.name.clone(),
             schemas: package.schemas.iter().map(|s| (&**s).into()).collect(),
+            manifest: package.manifest.clone(),
+        }
+    }
+}
+
+#[derive(TS, Serialize, Debug)]
+#[ts(export)]
+#[serde(rename = "Engine")]
+pub struct RawEngine {
+    pub package: String,
+    pub status: EngineStatus,
+}
+
+impl From<&Package> for RawEngine {
+    fn from(package: &Package) -> Self {
+        Self {
+            package: package.name.clone(),
+            status: package.engine_status(),
         }
     }
 }
@@ -11,7 +11,8 @@ pub enum RawNodeSchemaType {
     Base,
     Exec,
     Event,
-    Pure
+    Pure,
+    Flow,
 }
 
 #[derive(TS, Serialize, Debug)]
@@ -24,7 +25,7 @@ pub struct RawNodeSchema {
     pub t: RawNodeSchemaType,
 }
 
-#[derive(TS, Serialize, Debug)]
+#[derive(TS, Serialize, Debug, Clone)]
 pub struct RawNodeSchemaRef {
     pub name: String,
     pub package: String,
@@ -40,6 +41,7 @@ impl From<&NodeSchema> for RawNodeSchema {
                 NodeSchemaType::Exec { .. } => RawNodeSchemaType::Exec,
                 NodeSchemaType::Event { .. } => RawNodeSchemaType::Event,
                 NodeSchemaType::Pure { .. } => RawNodeSchemaType::Pure,
+                NodeSchemaType::Flow { .. } => RawNodeSchemaType::Flow,
             },
         }
     }
@@ -1,20 +1,52 @@
 use std::any::Any;
 use std::collections::HashMap;
+use std::ffi::CStr;
+use std::fs;
+use std::path::Path;
 use std::sync::Arc;
+use std::time::Duration;
 
 use crate::api::{Request, Response};
+use crate::custom_event::{custom_event_schemas, CustomEvent};
+use crate::debugger::{Debugger, Resume};
+use crate::discovery::{
+    discover_packages, shadow_copy, PackageDir, PackageLoadFailure, PackageManifest, PackageWatcher,
+};
+use crate::engine::{EngineStatus, RestartPolicy};
+use crate::error::CoreError;
+use crate::execution::{ConcurrencyPolicy, ExecutionLimits, ExecutionSource, Executions};
+use crate::executor::Executor;
+use crate::function::{function_schemas, FunctionParameter, FunctionSignature, FUNCTION_PACKAGE};
 use crate::graph::Graph;
+use crate::history::{Command, History, Transaction};
 use crate::io::{Input, Output};
-use crate::node::{Node, Position};
-use crate::package::{Engine, Package};
-use crate::ExecuteFn;
-use macrograph_package_api::engine::{EngineContext, Event};
-use macrograph_package_api::package::Package as ApiPackage;
+use crate::node::{update_wildcards, Node, Position};
+use crate::notification::{CoreNotification, Notifier};
+use crate::package::{Engine, LoadPackageError, Package};
+use crate::project::{
+    LoadProjectReport, MissingNode, ProjectError, SerializedConnection, SerializedFragment,
+    SerializedGraph, SerializedNode, SerializedProject, PROJECT_VERSION,
+};
+use crate::scheduler::{time_schemas, Scheduler, TIME_PACKAGE};
+use crate::schema::NodeSchema;
+use crate::trace::{TraceEvent, Tracer};
+use crate::variable::{
+    variable_schemas, Variable, VariableAccess, VariableId, VariableScope, Variables,
+};
+use libloading::Library;
+use macrograph_package_api::engine::Event;
+use macrograph_package_api::package::{Package as ApiPackage, PackageDescriptor};
+use macrograph_package_api::primitive::Primitive;
 use macrograph_package_api::schema::NodeSchemaType;
 use macrograph_package_api::value::types::ValueType;
-use macrograph_package_api::{ExecuteContext, Value};
+use serde::Serialize;
 use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
-use tokio::sync::oneshot;
+use tokio::sync::{broadcast, oneshot};
+use ts_rs::TS;
+
+// How long a reloaded package's engine and runtime get to stop before its old
+// library is leaked instead of unloaded
+const UNLOAD_TIMEOUT: Duration = Duration::from_secs(5);
 
 pub struct Core {
     pub graphs: HashMap<i32, Graph>,
@@ -25,6 +57,21 @@ pub struct Core {
     ),
     event_channel: (UnboundedSender<Event>, UnboundedReceiver<Event>),
     graph_id_counter: i32,
+    pub engine_restart_policy: RestartPolicy,
+    package_watcher: Option<PackageWatcher>,
+    pub execution_limits: ExecutionLimits,
+    executions: Executions,
+    event_policies: HashMap<(String, String), ConcurrencyPolicy>,
+    event_queues: HashMap<ExecutionSource, Arc<tokio::sync::Mutex<()>>>,
+    tracer: Tracer,
+    debugger: Debugger,
+    notifier: Notifier,
+    histories: HashMap<i32, History>,
+    variables: Variables,
+    variable_changes: UnboundedReceiver<Variable>,
+    custom_events: Vec<CustomEvent>,
+    scheduler: Scheduler,
+    timer_fires: UnboundedReceiver<Arc<Node>>,
 }
 
 struct WrappedRequest {
@@ -41,39 +88,105 @@ impl WrappedRequest {
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(TS, Serialize, Debug, PartialEq)]
+#[ts(export)]
+#[serde(tag = "type", content = "data")]
 pub enum ConnectIOError {
-    InvalidNodes { input: bool, output: bool },
-    InvalidIO { input: bool, output: bool },
+    InvalidGraph,
+    InvalidNodes {
+        input: bool,
+        output: bool,
+    },
+    InvalidIO {
+        input: bool,
+        output: bool,
+    },
+    TypeMismatch {
+        expected: ValueType,
+        found: ValueType,
+    },
+    KindMismatch,
+    SameNode,
+    WouldCreateCycle,
 }
 
 pub struct CoreController {
     request_sender: UnboundedSender<WrappedRequest>,
+    executions: Executions,
+    tracer: Tracer,
+    notifier: Notifier,
 }
 
 impl CoreController {
     pub async fn send(&self, request: Request) -> Response {
+        // Running executions are shared with the controller, so cancelling
+        // doesn't have to wait in line behind the requests before it
+        if let Request::CancelExecution { execution } = request {
+            return match self.executions.cancel(execution) {
+                Ok(()) => Response::CancelExecution,
+                Err(e) => Response::Error {
+                    request: request.name().to_string(),
+                    message: e.to_string(),
+                    kind: e,
+                },
+            };
+        }
+
         let (sender, recv) = oneshot::channel();
         let wrapped = WrappedRequest::new(request, sender);
 
-        self.request_sender.send(wrapped);
+        self.request_sender.send(wrapped).ok();
 
         let resp = recv.await;
 
         resp.unwrap()
     }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<CoreNotification> {
+        self.notifier.subscribe()
+    }
+
+    // Trace events are only sent while a Request::SubscribeExecutionTrace
+    // hasn't been matched by an unsubscribe
+    pub fn subscribe_execution_trace(&self) -> broadcast::Receiver<TraceEvent> {
+        self.tracer.subscribe()
+    }
 }
 
 impl Core {
     pub fn new() -> Self {
+        let (variables, variable_changes) = Variables::new();
+        let (scheduler, timer_fires) = Scheduler::new();
+
         let mut ret = Self {
             graphs: HashMap::new(),
             packages: vec![],
             request_channel: unbounded_channel(),
             event_channel: unbounded_channel(),
             graph_id_counter: 0,
+            engine_restart_policy: RestartPolicy::default(),
+            package_watcher: None,
+            execution_limits: ExecutionLimits::default(),
+            executions: Executions::default(),
+            event_policies: HashMap::new(),
+            event_queues: HashMap::new(),
+            tracer: Tracer::new(),
+            debugger: Debugger::default(),
+            notifier: Notifier::new(),
+            histories: HashMap::new(),
+            variables,
+            variable_changes,
+            custom_events: vec![],
+            scheduler,
+            timer_fires,
         };
 
+        ret.packages.push(ApiPackage::new(FUNCTION_PACKAGE).into());
+
+        let mut time_package: Package = ApiPackage::new(TIME_PACKAGE).into();
+        time_package.schemas = time_schemas(&ret.scheduler);
+        ret.packages.push(time_package);
+
         ret.create_graph("Graph 0".into());
         ret.create_graph("Graph 1".into());
 
@@ -95,13 +208,414 @@ impl Core {
         self.graphs.get_mut(&id)
     }
 
-    pub fn load_library(&mut self, path: &str) {
-        unsafe {
-            let lib = libloading::Library::new(path).unwrap();
-            let create_package: libloading::Symbol<fn() -> ApiPackage> =
-                lib.get(b"create_package").unwrap();
-            let p = create_package();
-            self.packages.push(p.into());
+    pub fn serialize_project(&self) -> SerializedProject {
+        let mut graphs: Vec<SerializedGraph> = self.graphs.values().map(|g| g.into()).collect();
+        graphs.sort_by_key(|g| g.id);
+
+        SerializedProject {
+            version: PROJECT_VERSION,
+            graphs,
+            variables: self.variables.all(),
+            custom_events: self.custom_events.clone(),
+        }
+    }
+
+    pub fn save_project(&self, path: &Path) -> Result<(), ProjectError> {
+        let project = serde_json::to_string_pretty(&self.serialize_project())?;
+
+        std::fs::write(path, project)?;
+
+        Ok(())
+    }
+
+    pub async fn load_project(&mut self, path: &Path) -> Result<LoadProjectReport, ProjectError> {
+        let project = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+
+        self.load_serialized_project(project).await
+    }
+
+    // Replaces all graphs with the ones in the project. Node IDs are reassigned
+    // by Graph::create_node, so connections are remapped as nodes are created.
+    pub async fn load_serialized_project(
+        &mut self,
+        project: SerializedProject,
+    ) -> Result<LoadProjectReport, ProjectError> {
+        if project.version > PROJECT_VERSION {
+            return Err(ProjectError::UnsupportedVersion(project.version));
+        }
+
+        let mut report = LoadProjectReport::default();
+
+        self.graphs.clear();
+        self.histories.clear();
+        self.scheduler.clear();
+        self.graph_id_counter = 0;
+
+        self.variables.clear();
+        self.custom_events.clear();
+
+        if let Some(package) = self.package_mut(FUNCTION_PACKAGE) {
+            package.schemas.retain(|schema| !schema.is_generated());
+        }
+
+        // Functions need their schemas before any graph can call them
+        for graph in &project.graphs {
+            let mut new_graph = Graph::new(graph.id, graph.name.clone());
+            new_graph.function = graph.function.clone();
+
+            self.graphs.insert(graph.id, new_graph);
+            self.graph_id_counter = self.graph_id_counter.max(graph.id + 1);
+        }
+
+        for graph in &project.graphs {
+            self.update_function_schemas(graph.id).await;
+        }
+
+        for variable in project.variables {
+            if let VariableScope::Graph { graph } = variable.scope {
+                if self.graph(graph).is_none() {
+                    continue;
+                }
+            }
+
+            self.add_variable_schemas(&variable);
+            self.variables.insert(variable);
+        }
+
+        for event in project.custom_events {
+            let schemas = custom_event_schemas(&event, &self.event_channel.0);
+
+            if let Some(package) = self.package_mut(FUNCTION_PACKAGE) {
+                package.schemas.extend(schemas);
+            }
+
+            self.custom_events.push(event);
+        }
+
+        for graph in project.graphs {
+            let mut node_ids = HashMap::new();
+            let mut created_nodes = vec![];
+
+            for node in graph.nodes {
+                let created = self
+                    .create_node(graph.id, &node.package, &node.schema, node.position)
+                    .await;
+
+                match created {
+                    Ok(created) => {
+                        created.set_breakpoint(node.breakpoint);
+
+                        created.restore(node.properties, node.wildcards);
+
+                        if let Some(state) = node.state {
+                            created.state.lock().unwrap().load(state);
+                        }
+
+                        for (input, value) in node.default_values {
+                            if let Some(input) = created.find_data_input(&input) {
+                                input.set_default_value(value);
+                                input.reset_value();
+                            }
+                        }
+
+                        node_ids.insert(node.id, created.id);
+                        created_nodes.push(created);
+                    }
+                    Err(_) => report.missing_nodes.push(MissingNode {
+                        graph: graph.id,
+                        node: node.id,
+                        package: node.package,
+                        schema: node.schema,
+                    }),
+                }
+            }
+
+            for connection in graph.connections {
+                let (output_node, input_node) = match (
+                    node_ids.get(&connection.output_node),
+                    node_ids.get(&connection.input_node),
+                ) {
+                    (Some(output_node), Some(input_node)) => (*output_node, *input_node),
+                    // Already reported through missing_nodes
+                    _ => continue,
+                };
+
+                let res = self.connect_io(
+                    graph.id,
+                    output_node,
+                    &connection.output,
+                    input_node,
+                    &connection.input,
+                );
+
+                if res.is_err() {
+                    report.failed_connections.push(connection);
+                }
+            }
+
+            // Wildcards that were resolved by connections which failed
+            let released = created_nodes
+                .iter()
+                .flat_map(|node| node.released_wildcard_sets());
+
+            for (node, wildcards) in update_wildcards(released, |wildcards, group| {
+                wildcards.remove(&group);
+            }) {
+                node.set_wildcards(wildcards);
+            }
+        }
+
+        Ok(report)
+    }
+
+    pub fn load_library(&mut self, path: impl AsRef<Path>) -> Result<(), LoadPackageError> {
+        let package = self.open_library(path.as_ref(), None, None)?;
+
+        self.packages.push(package);
+
+        Ok(())
+    }
+
+    // Loads all packages found in the given directories, making sure that
+    // packages are loaded after the packages their manifests depend on
+    pub fn load_package_dirs(&mut self, dirs: &[PackageDir]) -> Vec<PackageLoadFailure> {
+        let (mut pending, mut failures) = discover_packages(dirs);
+
+        loop {
+            let (ready, waiting): (Vec<_>, Vec<_>) = pending.into_iter().partition(|candidate| {
+                candidate.manifest.as_ref().map_or(true, |manifest| {
+                    manifest
+                        .dependencies
+                        .iter()
+                        .all(|dependency| self.package(dependency).is_some())
+                })
+            });
+
+            pending = waiting;
+
+            if ready.is_empty() {
+                break;
+            }
+
+            for candidate in ready {
+                let result = match &candidate.manifest {
+                    Some(manifest) => manifest.check_core_version(),
+                    None => Ok(()),
+                }
+                .and_then(|_| self.open_library(&candidate.library, candidate.manifest, None));
+
+                match result {
+                    Ok(package) => self.packages.push(package),
+                    Err(error) => failures.push(PackageLoadFailure {
+                        path: candidate.library,
+                        error,
+                    }),
+                }
+            }
+        }
+
+        for candidate in pending {
+            let missing = candidate
+                .manifest
+                .map(|manifest| {
+                    manifest
+                        .dependencies
+                        .into_iter()
+                        .filter(|dependency| self.package(dependency).is_none())
+                        .collect()
+                })
+                .unwrap_or_default();
+
+            failures.push(PackageLoadFailure {
+                path: candidate.library,
+                error: LoadPackageError::MissingDependencies(missing),
+            });
+        }
+
+        failures
+    }
+
+    // replacing is the name of the package being reloaded, which the library
+    // must provide instead of a package that isn't loaded yet
+    fn open_library(
+        &self,
+        path: &Path,
+        manifest: Option<PackageManifest>,
+        replacing: Option<&str>,
+    ) -> Result<Package, LoadPackageError> {
+        let library = unsafe { Library::new(path) }.map_err(LoadPackageError::Open)?;
+
+        let expected = PackageDescriptor::current();
+
+        // Nothing else in the library can be trusted until the descriptor has been checked
+        let descriptor = unsafe {
+            let symbol = library
+                .get::<*const PackageDescriptor>(b"MACROGRAPH_PACKAGE_API_VERSION\0")
+                .map_err(|_| LoadPackageError::MissingDescriptor)?;
+
+            &**symbol
+        };
+
+        if descriptor.api_version != expected.api_version {
+            return Err(LoadPackageError::ApiVersionMismatch {
+                expected: expected.api_version,
+                found: descriptor.api_version,
+            });
+        }
+
+        let (expected_rustc, found_rustc) = unsafe {
+            (
+                CStr::from_ptr(expected.rustc_version),
+                CStr::from_ptr(descriptor.rustc_version),
+            )
+        };
+
+        if expected_rustc != found_rustc {
+            return Err(LoadPackageError::RustcVersionMismatch {
+                expected: expected_rustc.to_string_lossy().into(),
+                found: found_rustc.to_string_lossy().into(),
+            });
+        }
+
+        let api_package = unsafe {
+            let create_package = library
+                .get::<fn() -> ApiPackage>(b"create_package\0")
+                .map_err(|_| LoadPackageError::MissingEntryPoint)?;
+
+            create_package()
+        };
+
+        let error = match (&manifest, replacing) {
+            (_, Some(replacing)) if replacing != api_package.name => {
+                Some(LoadPackageError::NameChanged {
+                    expected: replacing.to_string(),
+                    found: api_package.name.clone(),
+                })
+            }
+            (_, None) if self.package(&api_package.name).is_some() => {
+                Some(LoadPackageError::AlreadyLoaded(api_package.name.clone()))
+            }
+            (Some(manifest), _) if manifest.name != api_package.name => {
+                Some(LoadPackageError::NameMismatch {
+                    manifest: manifest.name.clone(),
+                    package: api_package.name.clone(),
+                })
+            }
+            _ => None,
+        };
+
+        if let Some(error) = error {
+            // Dropping a runtime inside of an async context panics, and since the
+            // runtime's threads run code from the library it has to stay loaded
+            api_package.runtime.shutdown_background();
+            std::mem::forget(library);
+            return Err(error);
+        }
+
+        let mut package = Package::new(api_package, Some(Arc::new(library)));
+        package.manifest = manifest;
+        package.path = Some(path.to_path_buf());
+
+        Ok(package)
+    }
+
+    // Replaces a package with a fresh copy of its library, moving existing nodes
+    // over to the new schemas. Connections are kept for IO that still exists
+    // with the same type. Nodes whose schema no longer exists keep using the old one.
+    pub async fn reload_package(&mut self, name: &str) -> Result<(), CoreError> {
+        let index =
+            self.packages
+                .iter()
+                .position(|p| p.name == name)
+                .ok_or(CoreError::UnknownPackage {
+                    package: name.to_string(),
+                })?;
+
+        let path = self.packages[index]
+            .path
+            .clone()
+            .ok_or(CoreError::NotReloadable {
+                package: name.to_string(),
+            })?;
+        let manifest = self.packages[index].manifest.clone();
+
+        let mut package = shadow_copy(&path)
+            .and_then(|shadow| {
+                let result = self.open_library(&shadow, manifest, Some(name));
+                // Only succeeds on platforms that allow removing loaded libraries
+                fs::remove_file(&shadow).ok();
+                result
+            })
+            .map_err(|e| CoreError::LoadPackage {
+                package: name.to_string(),
+                message: e.to_string(),
+            })?;
+        package.path = Some(path);
+
+        let old = std::mem::replace(&mut self.packages[index], package);
+        let engine_started = matches!(old.engine, Some(Engine::Running { .. }));
+
+        for old_schema in &old.schemas {
+            let new_schema = match self.packages[index].schema(&old_schema.name) {
+                Some(schema) => schema.clone(),
+                None => {
+                    log::warn!(
+                        "Schema {} was removed from package {}, existing nodes will keep the old version",
+                        old_schema.name, name
+                    );
+                    continue;
+                }
+            };
+
+            for node in old_schema.instances.lock().await.iter() {
+                node.state.lock().unwrap().detach();
+            }
+
+            migrate_instances(old_schema, &new_schema).await;
+        }
+
+        old.unload(UNLOAD_TIMEOUT).await;
+
+        // Removed nodes kept by the history still use the old schemas
+        self.histories.clear();
+
+        if engine_started {
+            self.packages[index].start_engine(
+                self.event_channel.0.clone(),
+                self.notifier.clone(),
+                self.engine_restart_policy,
+            );
+        }
+
+        self.notifier.notify(CoreNotification::PackageReloaded {
+            package: name.to_string(),
+            graphs: self.graphs.values().map(|g| g.into()).collect(),
+        });
+
+        Ok(())
+    }
+
+    // Reloads packages whenever their library changes on disk, checking every period.
+    // Passing None stops watching.
+    pub fn watch_packages(&mut self, period: Option<Duration>) {
+        self.package_watcher = period.map(|period| {
+            let mut watcher = PackageWatcher::new(period);
+            watcher.changed(&self.packages);
+            watcher
+        });
+    }
+
+    async fn reload_changed_packages(&mut self) {
+        let changed = match &mut self.package_watcher {
+            Some(watcher) => watcher.changed(&self.packages),
+            None => return,
+        };
+
+        for package in changed {
+            match self.reload_package(&package).await {
+                Ok(()) => log::info!("Reloaded package {}", package),
+                Err(e) => log::error!("{}", e),
+            }
         }
     }
 
@@ -115,52 +629,107 @@ impl Core {
 
     pub fn setup(&mut self) {
         for package in &mut self.packages {
-            package.engine = if let Some(engine) = package.engine.take() {
-                match engine {
-                    Engine::Created { run, state } => {
-                        let (request_sender, request_receiver) = unbounded_channel();
-                        let handle = package.runtime.handle();
-                        let handle = handle.clone();
-                        let package = package.name.clone();
-                        let event_sender = self.event_channel.0.clone();
-
-                        std::thread::spawn(move || {
-                            run(EngineContext {
-                                initial_state: state,
-                                request_receiver,
-                                handle: handle.clone(),
-                                package,
-                                event_sender,
-                            });
-                        });
-
-                        Some(Engine::Running { request_sender })
-                    }
-                    _ => {
-                        println!("Attempted to setup an engine that has already been created");
-                        None
-                    }
+            package.start_engine(
+                self.event_channel.0.clone(),
+                self.notifier.clone(),
+                self.engine_restart_policy,
+            );
+        }
+    }
+
+    // Cancels all executions and stops every engine, waiting up to timeout for
+    // them to finish before unloading the packages. Engines that don't check
+    // for stop requests are left running, and their libraries loaded, until
+    // the process exits.
+    pub async fn shutdown(&mut self, timeout: Duration) {
+        self.executions.cancel(None).ok();
+        self.scheduler.clear();
+
+        for package in &mut self.packages {
+            package.stop_engine();
+        }
+
+        let deadline = tokio::time::Instant::now() + timeout;
+
+        while tokio::time::Instant::now() < deadline {
+            let engines_stopped = self.packages.iter().all(|package| {
+                !matches!(
+                    package.engine_status(),
+                    EngineStatus::Running | EngineStatus::Restarting
+                )
+            });
+
+            if engines_stopped && self.executions.is_empty() {
+                break;
+            }
+
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+
+        for package in self.packages.drain(..) {
+            package
+                .unload(deadline.saturating_duration_since(tokio::time::Instant::now()))
+                .await;
+        }
+    }
+
+    pub fn restart_engine(&self, package: &str) -> Result<(), CoreError> {
+        let package = self.package(package).ok_or(CoreError::UnknownPackage {
+            package: package.to_string(),
+        })?;
+
+        match &package.engine {
+            Some(Engine::Running { restart_sender, .. }) => {
+                if package.engine_status() == EngineStatus::Running {
+                    return Err(CoreError::EngineRunning {
+                        package: package.name.clone(),
+                    });
                 }
-            } else {
-                None
+
+                restart_sender.send(()).ok();
+
+                Ok(())
             }
+            _ => Err(CoreError::NoEngine {
+                package: package.name.clone(),
+            }),
         }
     }
 
     async fn process_request(&mut self, request: WrappedRequest) {
+        let name = request.inner.name();
+
+        let res = match self.handle_request(request.inner).await {
+            Ok(res) => res,
+            Err(e) => Response::Error {
+                request: name.to_string(),
+                message: e.to_string(),
+                kind: e,
+            },
+        };
+
+        request.sender.send(res).ok();
+    }
+
+    async fn handle_request(&mut self, request: Request) -> Result<Response, CoreError> {
         use Request::*;
 
-        let res = match request.inner {
+        Ok(match request {
             CreateNode {
                 graph,
                 package,
                 schema,
                 position,
             } => {
-                let node = self
-                    .create_node(graph, &package, &schema, position)
-                    .await
-                    .unwrap();
+                let node = self.create_node(graph, &package, &schema, position).await?;
+
+                self.history(graph)
+                    .record(vec![Command::AddNode(node.clone())]);
+                self.notifier.notify(CoreNotification::NodeCreated {
+                    graph,
+                    node: node.as_ref().into(),
+                });
+
                 let inputs = node.inputs.lock().unwrap();
                 let outputs = node.outputs.lock().unwrap();
 
@@ -175,22 +744,39 @@ impl Core {
                 node,
                 position,
             } => {
-                self.graph_mut(graph)
-                    .and_then(|g| g.node(node))
-                    .map(|node| node.set_position(position));
+                let from = self.try_node(graph, node)?.position.lock().unwrap().clone();
+
+                self.apply(
+                    graph,
+                    vec![Command::MoveNode {
+                        node,
+                        from,
+                        to: position,
+                    }],
+                )
+                .await?;
                 Response::SetNodePosition
             }
             DeleteNode { graph, node } => {
-                self.delete_node(graph, node);
+                let transaction = delete_node_transaction(self.try_node(graph, node)?);
+
+                self.apply(graph, transaction).await?;
                 Response::DeleteNode
             }
             CreateGraph => {
                 let name = format!("Graph {}", self.graphs.len());
                 let id = self.create_graph(name.clone());
+                self.notifier.notify(CoreNotification::GraphCreated {
+                    id,
+                    name: name.clone(),
+                });
                 Response::CreateGraph { id, name }
             }
             RenameGraph { id, name } => {
-                self.graph_mut(id).map(|g| g.name = name);
+                let from = self.try_graph(id)?.name.clone();
+
+                self.apply(id, vec![Command::Rename { from, to: name }])
+                    .await?;
                 Response::RenameGraph
             }
             ConnectIO {
@@ -200,9 +786,36 @@ impl Core {
                 input_node,
                 input,
             } => {
-                self.connect_io(graph, output_node, &output, input_node, &input)
-                    .unwrap();
+                let (output_io, input_io) =
+                    self.check_connection(graph, output_node, &output, input_node, &input)?;
+
+                // Inputs and exec outputs only have a single connection, so
+                // connecting them replaces their existing connection
+                let mut replaced: Vec<_> = input_io
+                    .connected_output()
+                    .and_then(|output| SerializedConnection::new(&output, &input_io))
+                    .into_iter()
+                    .collect();
+
+                if let Output::Exec(_) = output_io {
+                    for input in output_io.connected_inputs() {
+                        replaced.extend(SerializedConnection::new(&output_io, &input));
+                    }
+                }
+
+                replaced.dedup();
+
+                let mut transaction: Transaction =
+                    replaced.into_iter().map(Command::Disconnect).collect();
+                transaction.extend(Self::resolve_wildcards(&output_io, &input_io));
+                transaction.push(Command::Connect(SerializedConnection {
+                    output_node,
+                    output,
+                    input_node,
+                    input,
+                }));
 
+                self.apply(graph, transaction).await?;
                 Response::ConnectIO
             }
             DisconnectIO {
@@ -211,8 +824,40 @@ impl Core {
                 io,
                 is_input,
             } => {
-                self.disconnect_io(graph, node, &io, is_input).unwrap();
+                let node_ref = self.try_node(graph, node)?;
 
+                let unknown_io = || CoreError::UnknownIO {
+                    node,
+                    io: io.clone(),
+                    is_input,
+                };
+
+                let connections = match is_input {
+                    true => {
+                        let input = node_ref.find_input(&io).ok_or_else(unknown_io)?;
+
+                        input
+                            .connected_output()
+                            .and_then(|output| SerializedConnection::new(&output, &input))
+                            .into_iter()
+                            .collect()
+                    }
+                    false => {
+                        let output = node_ref.find_output(&io).ok_or_else(unknown_io)?;
+
+                        output
+                            .connected_inputs()
+                            .iter()
+                            .filter_map(|input| SerializedConnection::new(&output, input))
+                            .collect::<Vec<_>>()
+                    }
+                };
+
+                self.apply(
+                    graph,
+                    connections.into_iter().map(Command::Disconnect).collect(),
+                )
+                .await?;
                 Response::DisconnectIO
             }
             SetDefaultValue {
@@ -221,33 +866,269 @@ impl Core {
                 input,
                 value,
             } => {
-                let input = self
-                    .graph(graph)
-                    .unwrap()
-                    .node(node)
-                    .unwrap()
-                    .find_data_input(&input)
-                    .unwrap();
-
-                if input.r#type == ValueType::Primitive(value.r#type()) {
-                    input.set_default_value(value);
+                let data_input = self.try_node(graph, node)?.find_data_input(&input).ok_or(
+                    CoreError::UnknownIO {
+                        node,
+                        io: input.clone(),
+                        is_input: true,
+                    },
+                )?;
+
+                let value_type = ValueType::Primitive(value.r#type());
+
+                if data_input.r#type != value_type {
+                    return Err(CoreError::InvalidValue {
+                        expected: data_input.r#type,
+                        found: value_type,
+                    });
                 }
 
+                let from = data_input.default_value.load().as_ref().clone();
+
+                self.apply(
+                    graph,
+                    vec![Command::SetDefaultValue {
+                        node,
+                        input,
+                        from,
+                        to: value,
+                    }],
+                )
+                .await?;
+
                 Response::SetDefaultValue
             }
+            SetNodeProperty {
+                graph,
+                node,
+                property,
+                value,
+            } => {
+                let node = self.try_node(graph, node)?.clone();
+                let from = node
+                    .find_property(&property)
+                    .ok_or(CoreError::UnknownProperty {
+                        node: node.id,
+                        property: property.clone(),
+                    })?;
+
+                if !from.kind.accepts(&value) {
+                    return Err(CoreError::InvalidProperty { property, value });
+                }
+
+                // Disconnecting first means undoing the change reconnects
+                // the IO once it's been restored
+                let mut transaction: Transaction = node
+                    .dropped_connections(&node.build_with_property(&property, value.clone()))
+                    .into_iter()
+                    .map(Command::Disconnect)
+                    .collect();
+                transaction.push(Command::SetProperty {
+                    node: node.id,
+                    property,
+                    from: from.value,
+                    to: value,
+                });
+
+                self.apply(graph, transaction).await?;
+
+                Response::SetNodeProperty
+            }
             GetPackages => Response::GetPackages {
                 packages: self.packages.iter().map(|p| p.into()).collect(),
             },
             GetProject => Response::GetProject {
                 graphs: self.graphs.values().map(|g| g.into()).collect(),
+                variables: self.variables.all(),
+                custom_events: self.custom_events.clone(),
             },
+            SaveProject { path } => {
+                self.save_project(Path::new(&path))?;
+                Response::SaveProject
+            }
+            LoadProject { path } => {
+                let report = self.load_project(Path::new(&path)).await?;
+                let graphs: Vec<_> = self.graphs.values().map(|g| g.into()).collect();
+
+                self.notifier.notify(CoreNotification::ProjectLoaded {
+                    graphs: graphs.clone(),
+                });
+
+                Response::LoadProject { graphs, report }
+            }
             Reset { graph } => {
-                self.graph_mut(graph).map(|g| g.reset());
+                let mut nodes: Vec<_> = self.try_graph(graph)?.nodes.values().collect();
+                nodes.sort_by_key(|node| node.id);
+
+                // Undoing the reset brings back the same nodes, which shouldn't
+                // remember anything from before
+                for node in &nodes {
+                    node.state.lock().unwrap().clear();
+                }
+
+                // Every connection is between two of the graph's nodes, so
+                // each is found once by looking at inputs alone
+                let mut transaction: Transaction = nodes
+                    .iter()
+                    .flat_map(|node| node.input_connections())
+                    .map(Command::Disconnect)
+                    .collect();
+                transaction.extend(nodes.into_iter().cloned().map(Command::RemoveNode));
+
+                self.apply(graph, transaction).await?;
+                self.notifier.notify(CoreNotification::GraphReset { graph });
                 Response::Reset
             }
-        };
+            SetGraphFunction { graph, function } => {
+                self.set_graph_function(graph, function).await?;
+                Response::SetGraphFunction
+            }
+            CopyNodes { graph, nodes } => Response::CopyNodes {
+                fragment: self.copy_nodes(graph, &nodes)?,
+            },
+            PasteNodes {
+                graph,
+                fragment,
+                offset,
+            } => {
+                let nodes = self.paste_nodes(graph, fragment, offset).await?;
+
+                Response::PasteNodes {
+                    nodes: nodes.iter().map(|node| node.as_ref().into()).collect(),
+                }
+            }
+            CreateVariable { name, scope, value } => Response::CreateVariable {
+                variable: self.create_variable(name, scope, value)?,
+            },
+            DeleteVariable { variable } => {
+                self.delete_variable(variable)?;
+                Response::DeleteVariable
+            }
+            SetVariableValue { variable, value } => {
+                self.set_variable_value(variable, value)?;
+                Response::SetVariableValue
+            }
+            SetCustomEvent { name, payload } => {
+                self.set_custom_event(&name, payload).await?;
+                Response::SetCustomEvent
+            }
+            Undo { graph } => {
+                let transaction = self
+                    .history(graph)
+                    .next_undo()
+                    .ok_or(CoreError::NothingToUndo { graph })?;
+
+                self.replay(graph, transaction).await?;
+                self.history(graph).undone();
+                Response::Undo
+            }
+            Redo { graph } => {
+                let transaction = self
+                    .history(graph)
+                    .next_redo()
+                    .ok_or(CoreError::NothingToRedo { graph })?;
+
+                self.replay(graph, transaction).await?;
+                self.history(graph).redone();
+                Response::Redo
+            }
+            GetEngineStatus => Response::GetEngineStatus {
+                engines: self
+                    .packages
+                    .iter()
+                    .filter(|p| p.engine.is_some())
+                    .map(|p| p.into())
+                    .collect(),
+            },
+            RestartEngine { package } => {
+                self.restart_engine(&package)?;
+                Response::RestartEngine
+            }
+            SetEventPolicy {
+                package,
+                event,
+                policy,
+            } => {
+                self.set_event_policy(&package, &event, policy)?;
+                Response::SetEventPolicy
+            }
+            SubscribeExecutionTrace { subscribe } => {
+                self.tracer.set_subscribed(subscribe);
+                Response::SubscribeExecutionTrace
+            }
+            SetBreakpoint {
+                graph,
+                node,
+                enabled,
+            } => {
+                self.try_node(graph, node)?.set_breakpoint(enabled);
+                self.notifier.notify(CoreNotification::BreakpointSet {
+                    graph,
+                    node,
+                    enabled,
+                });
+                Response::SetBreakpoint
+            }
+            GetPausedExecutions => Response::GetPausedExecutions {
+                executions: self.debugger.paused(),
+            },
+            Continue { execution } => {
+                self.debugger.resume(execution, Resume::Continue)?;
+                Response::Continue
+            }
+            StepOver { execution } => {
+                self.debugger.resume(execution, Resume::StepOver)?;
+                Response::StepOver
+            }
+            InspectValue {
+                graph,
+                node,
+                io,
+                is_input,
+            } => {
+                let node_ref = self.try_node(graph, node)?;
+
+                let value = if is_input {
+                    node_ref
+                        .find_data_input(&io)
+                        .map(|input| input.value.load().as_ref().clone())
+                } else {
+                    node_ref
+                        .find_data_output(&io)
+                        .map(|output| output.value.load().as_ref().clone())
+                };
+
+                Response::InspectValue {
+                    value: value.ok_or(CoreError::UnknownIO { node, io, is_input })?,
+                }
+            }
+            CancelExecution { execution } => {
+                self.executions.cancel(execution)?;
+                Response::CancelExecution
+            }
+            ReloadPackage { package } => {
+                self.reload_package(&package).await?;
+
+                Response::ReloadPackage {
+                    graphs: self.graphs.values().map(|g| g.into()).collect(),
+                }
+            }
+        })
+    }
+
+    fn try_graph(&self, graph: i32) -> Result<&Graph, CoreError> {
+        self.graph(graph).ok_or(CoreError::UnknownGraph { graph })
+    }
+
+    fn try_graph_mut(&mut self, graph: i32) -> Result<&mut Graph, CoreError> {
+        self.graph_mut(graph)
+            .ok_or(CoreError::UnknownGraph { graph })
+    }
 
-        request.sender.send(res);
+    fn try_node(&self, graph: i32, node: i32) -> Result<&Arc<Node>, CoreError> {
+        self.try_graph(graph)?
+            .node(node)
+            .ok_or(CoreError::UnknownNode { graph, node })
     }
 
     pub(crate) async fn create_node(
@@ -256,43 +1137,684 @@ impl Core {
         package: &str,
         schema: &str,
         position: Position,
-    ) -> Option<Arc<Node>> {
-        let schema = self.package(&package).unwrap().schema(&schema); //and_then(|p| p.schema(&schema));
+    ) -> Result<Arc<Node>, CoreError> {
+        let schema = self.try_schema(graph, package, schema)?;
+        let node = self.try_graph_mut(graph)?.create_node(&schema, position);
 
-        if let Some(schema) = schema {
-            let schema = schema.clone();
-            let node = self
-                .graph_mut(graph)
-                .unwrap()
-                .create_node(&schema, position);
-            let mut instances = schema.instances.lock().await;
+        schema.instances.lock().await.insert(node.clone());
+        self.scheduler.add_node(&node);
 
-            instances.insert(node.clone());
+        Ok(node)
+    }
 
-            return Some(node);
+    pub async fn set_graph_function(
+        &mut self,
+        graph: i32,
+        function: Option<FunctionSignature>,
+    ) -> Result<(), CoreError> {
+        if let Some(signature) = &function {
+            check_parameters(&signature.inputs)?;
+            check_parameters(&signature.outputs)?;
         }
 
-        None
-    }
+        self.try_graph_mut(graph)?.function = function;
+        self.update_function_schemas(graph).await;
 
-    fn delete_node(&mut self, graph: i32, node: i32) {
-        self.graph_mut(graph).unwrap().delete_node(node)
+        // Removed nodes kept by the history may use the old schemas
+        self.histories.clear();
+
+        Ok(())
     }
 
-    pub fn connect_io(
+    // Creates, updates or deletes (if payload is None) a custom event. Nodes of
+    // deleted events keep the old schemas, like nodes of deleted functions.
+    pub async fn set_custom_event(
         &mut self,
-        graph: i32,
-        output_node: i32,
-        output: &str,
+        name: &str,
+        payload: Option<Vec<FunctionParameter>>,
+    ) -> Result<(), CoreError> {
+        let index = self.custom_events.iter().position(|e| e.name == name);
+
+        let event = match (payload, index) {
+            (Some(payload), index) => {
+                check_parameters(&payload)?;
+
+                // New events can't share a name with any other schema in the package
+                let call = format!("Call {}", name);
+                let taken = self.package(FUNCTION_PACKAGE).is_some_and(|package| {
+                    package
+                        .schemas
+                        .iter()
+                        .any(|schema| schema.name == name || schema.name == call)
+                });
+
+                if index.is_none() && (name.is_empty() || taken) {
+                    return Err(CoreError::InvalidCustomEvent {
+                        name: name.to_string(),
+                    });
+                }
+
+                Some(CustomEvent {
+                    name: name.to_string(),
+                    payload,
+                })
+            }
+            (None, Some(_)) => None,
+            (None, None) => {
+                return Err(CoreError::UnknownSchema {
+                    package: FUNCTION_PACKAGE.to_string(),
+                    schema: name.to_string(),
+                })
+            }
+        };
+
+        match (&event, index) {
+            (Some(event), Some(index)) => self.custom_events[index] = event.clone(),
+            (Some(event), None) => self.custom_events.push(event.clone()),
+            (None, Some(index)) => {
+                self.custom_events.remove(index);
+            }
+            (None, None) => {}
+        }
+
+        let new_schemas = event
+            .as_ref()
+            .map(|event| custom_event_schemas(event, &self.event_channel.0));
+
+        let package = match self.package_mut(FUNCTION_PACKAGE) {
+            Some(package) => package,
+            None => return Ok(()),
+        };
+
+        let (old_schemas, schemas) = package.schemas.drain(..).partition(|schema| {
+            schema.custom_event().map(|e| e.event().name.as_str()) == Some(name)
+        });
+        package.schemas = schemas;
+
+        if let Some(new_schemas) = new_schemas {
+            package.schemas.extend(new_schemas.iter().cloned());
+
+            // Both are in the order custom_event_schemas creates them
+            for (old, new) in old_schemas.iter().zip(&new_schemas) {
+                migrate_instances(old, new).await;
+            }
+        }
+
+        // Removed nodes kept by the history may use the old schemas
+        self.histories.clear();
+        self.notify_project_schemas();
+
+        Ok(())
+    }
+
+    // Replaces the graph's function schemas with ones matching its name and
+    // signature, updating the IO of nodes using them
+    async fn update_function_schemas(&mut self, graph: i32) {
+        let new_schemas = self.graph(graph).and_then(|g| {
+            g.function
+                .as_ref()
+                .map(|signature| function_schemas(graph, &g.name, signature))
+        });
+
+        let package = match self.package_mut(FUNCTION_PACKAGE) {
+            Some(package) => package,
+            None => return,
+        };
+
+        let (old_schemas, schemas) = package
+            .schemas
+            .drain(..)
+            .partition(|schema| schema.function().map(|f| f.graph()) == Some(graph));
+        package.schemas = schemas;
+
+        // Nodes of removed functions keep the old schemas
+        if let Some(new_schemas) = new_schemas {
+            package.schemas.extend(new_schemas.iter().cloned());
+
+            // Both are in the order function_schemas creates them
+            for (old, new) in old_schemas.iter().zip(&new_schemas) {
+                migrate_instances(old, new).await;
+            }
+        }
+
+        self.notify_project_schemas();
+    }
+
+    // Finds a schema that can be used in the graph. Graph variables in
+    // different graphs can share a name, so their schemas do too.
+    fn try_schema(
+        &self,
+        graph: i32,
+        package: &str,
+        schema: &str,
+    ) -> Result<Arc<NodeSchema>, CoreError> {
+        self.package(package)
+            .ok_or(CoreError::UnknownPackage {
+                package: package.to_string(),
+            })?
+            .schemas
+            .iter()
+            .find(|s| s.name == schema && s.available_in(graph))
+            .cloned()
+            .ok_or(CoreError::UnknownSchema {
+                package: package.to_string(),
+                schema: schema.to_string(),
+            })
+    }
+
+    pub fn create_variable(
+        &mut self,
+        name: String,
+        scope: VariableScope,
+        value: Primitive,
+    ) -> Result<Variable, CoreError> {
+        if let VariableScope::Graph { graph } = scope {
+            self.try_graph(graph)?;
+        }
+
+        let taken = self
+            .variables
+            .all()
+            .iter()
+            .any(|v| v.name == name && v.scope.overlaps(&scope));
+
+        if name.is_empty() || taken {
+            return Err(CoreError::InvalidVariable { name });
+        }
+
+        let variable = self.variables.create(name, scope, value);
+
+        self.add_variable_schemas(&variable);
+        self.notifier.notify(CoreNotification::VariableCreated {
+            variable: variable.clone(),
+        });
+        self.notify_project_schemas();
+
+        Ok(variable)
+    }
+
+    // Refuses to delete variables that nodes still use, since those nodes
+    // would no longer read or set anything
+    pub fn delete_variable(&mut self, variable: VariableId) -> Result<(), CoreError> {
+        let user = self.graphs.values().find_map(|graph| {
+            graph
+                .nodes
+                .values()
+                .find(|node| node.schema().variable().map(|v| v.variable) == Some(variable))
+                .map(|node| (graph.id, node.id))
+        });
+
+        if let Some((graph, node)) = user {
+            return Err(CoreError::VariableInUse {
+                variable,
+                graph,
+                node,
+            });
+        }
+
+        self.variables
+            .remove(variable)
+            .ok_or(CoreError::UnknownVariable { variable })?;
+
+        if let Some(package) = self.package_mut(FUNCTION_PACKAGE) {
+            package
+                .schemas
+                .retain(|schema| schema.variable().map(|v| v.variable) != Some(variable));
+        }
+
+        // Removed nodes kept by the history may use the variable
+        self.histories.clear();
+
+        self.notifier
+            .notify(CoreNotification::VariableDeleted { variable });
+        self.notify_project_schemas();
+
+        Ok(())
+    }
+
+    pub fn set_variable_value(
+        &mut self,
+        variable: VariableId,
+        value: Primitive,
+    ) -> Result<(), CoreError> {
+        self.variables.set(variable, value)
+    }
+
+    fn add_variable_schemas(&mut self, variable: &Variable) {
+        let schemas = variable_schemas(variable, &self.variables);
+
+        if let Some(package) = self.package_mut(FUNCTION_PACKAGE) {
+            package.schemas.extend(schemas);
+        }
+    }
+
+    // Tells clients that schemas the Core creates for the project have changed
+    fn notify_project_schemas(&self) {
+        self.notifier.notify(CoreNotification::PackageReloaded {
+            package: FUNCTION_PACKAGE.to_string(),
+            graphs: self.graphs.values().map(|g| g.into()).collect(),
+        });
+    }
+
+    pub fn copy_nodes(&self, graph: i32, nodes: &[i32]) -> Result<SerializedFragment, CoreError> {
+        let mut selected = nodes
+            .iter()
+            .map(|&node| self.try_node(graph, node))
+            .collect::<Result<Vec<_>, _>>()?;
+        selected.sort_by_key(|node| node.id);
+        selected.dedup_by_key(|node| node.id);
+
+        let mut nodes: Vec<SerializedNode> =
+            selected.iter().map(|node| node.as_ref().into()).collect();
+
+        if let Some(origin) = nodes
+            .iter()
+            .map(|node| node.position.clone())
+            .reduce(|a, b| a.min(&b))
+        {
+            for node in &mut nodes {
+                node.position = node.position.clone() - origin.clone();
+                node.breakpoint = false;
+                node.state = None;
+            }
+        }
+
+        let connections = selected
+            .iter()
+            .flat_map(|node| node.input_connections())
+            .filter(|connection| nodes.iter().any(|n| n.id == connection.output_node))
+            .collect();
+
+        Ok(SerializedFragment { nodes, connections })
+    }
+
+    // Creates new nodes for the fragment with their top left corner at offset,
+    // returning them in the same order as the fragment's nodes
+    pub async fn paste_nodes(
+        &mut self,
+        graph: i32,
+        fragment: SerializedFragment,
+        offset: Position,
+    ) -> Result<Vec<Arc<Node>>, CoreError> {
+        self.try_graph(graph)?;
+
+        // Checked up front so that either all of the nodes are pasted or none are
+        for node in &fragment.nodes {
+            self.try_schema(graph, &node.package, &node.schema)?;
+        }
+
+        let mut transaction = vec![];
+        let mut created = vec![];
+        let mut node_ids = HashMap::new();
+
+        for node in fragment.nodes {
+            let position = offset.clone() + node.position;
+            let new_node = match self
+                .create_node(graph, &node.package, &node.schema, position)
+                .await
+            {
+                Ok(node) => node,
+                Err(e) => {
+                    self.roll_back(graph, &transaction).await;
+                    return Err(e);
+                }
+            };
+
+            new_node.restore(node.properties, node.wildcards);
+
+            for (input, value) in node.default_values {
+                if let Some(input) = new_node.find_data_input(&input) {
+                    if input.r#type == ValueType::Primitive(value.r#type()) {
+                        input.set_default_value(value);
+                        input.reset_value();
+                    }
+                }
+            }
+
+            self.notifier.notify(CoreNotification::NodeCreated {
+                graph,
+                node: new_node.as_ref().into(),
+            });
+
+            node_ids.insert(node.id, new_node.id);
+            transaction.push(Command::AddNode(new_node.clone()));
+            created.push(new_node);
+        }
+
+        for connection in fragment.connections {
+            let (output_node, input_node) = match (
+                node_ids.get(&connection.output_node),
+                node_ids.get(&connection.input_node),
+            ) {
+                (Some(output_node), Some(input_node)) => (*output_node, *input_node),
+                _ => continue,
+            };
+
+            let command = Command::Connect(SerializedConnection {
+                output_node,
+                input_node,
+                ..connection
+            });
+
+            // Connections that are no longer valid are left out, like when loading a project
+            if self.apply_command(graph, &command).await.is_ok() {
+                transaction.push(command);
+            }
+        }
+
+        // Wildcards that were resolved by connections that weren't copied
+        let nodes = created.iter().map(|node| node.id).collect();
+        let released = self.release_wildcards(graph, nodes);
+
+        if let Err(e) = self.apply_commands(graph, &released).await {
+            self.roll_back(graph, &transaction).await;
+            return Err(e);
+        }
+
+        transaction.extend(released);
+        self.history(graph).record(transaction);
+
+        Ok(created)
+    }
+
+    fn history(&mut self, graph: i32) -> &mut History {
+        self.histories.entry(graph).or_default()
+    }
+
+    // Applies a transaction and records it in the graph's history
+    async fn apply(&mut self, graph: i32, mut transaction: Transaction) -> Result<(), CoreError> {
+        self.apply_commands(graph, &transaction).await?;
+
+        // Releasing wildcards is part of the transaction, so that undoing it
+        // resolves them again before reconnecting them
+        let disconnected = transaction
+            .iter()
+            .filter_map(|command| match command {
+                Command::Disconnect(connection) => {
+                    Some([connection.output_node, connection.input_node])
+                }
+                _ => None,
+            })
+            .flatten()
+            .collect();
+        let released = self.release_wildcards(graph, disconnected);
+
+        if let Err(e) = self.apply_commands(graph, &released).await {
+            self.roll_back(graph, &transaction).await;
+            return Err(e);
+        }
+
+        transaction.extend(released);
+        self.history(graph).record(transaction);
+
+        Ok(())
+    }
+
+    // Applies a transaction from the graph's history. If it can't be applied
+    // the graph no longer matches the history, so the history is discarded.
+    async fn replay(&mut self, graph: i32, transaction: Transaction) -> Result<(), CoreError> {
+        let result = self.apply_commands(graph, &transaction).await;
+
+        if result.is_err() {
+            self.histories.remove(&graph);
+        }
+
+        result
+    }
+
+    // Applies the commands in order. If one fails, the ones before it are
+    // rolled back so that the graph is left as it was.
+    async fn apply_commands(&mut self, graph: i32, commands: &[Command]) -> Result<(), CoreError> {
+        for (applied, command) in commands.iter().enumerate() {
+            if let Err(e) = self.apply_command(graph, command).await {
+                self.roll_back(graph, &commands[..applied]).await;
+                return Err(e);
+            }
+        }
+
+        Ok(())
+    }
+
+    // Undoes commands that were applied. If that fails too the graph no longer
+    // matches its history, so the history is discarded.
+    async fn roll_back(&mut self, graph: i32, commands: &[Command]) {
+        for command in commands.iter().rev() {
+            if self.apply_command(graph, &command.inverse()).await.is_err() {
+                self.histories.remove(&graph);
+                return;
+            }
+        }
+    }
+
+    async fn apply_command(&mut self, graph: i32, command: &Command) -> Result<(), CoreError> {
+        let notification = match command.clone() {
+            Command::AddNode(node) => {
+                self.try_graph_mut(graph)?.insert_node(node.clone());
+                node.schema().instances.lock().await.insert(node.clone());
+                self.scheduler.add_node(&node);
+
+                CoreNotification::NodeCreated {
+                    graph,
+                    node: node.as_ref().into(),
+                }
+            }
+            Command::RemoveNode(node) => {
+                self.try_graph_mut(graph)?
+                    .delete_node(node.id)
+                    .ok_or(CoreError::UnknownNode {
+                        graph,
+                        node: node.id,
+                    })?;
+                // The history keeps the node alive, so events mustn't find it
+                node.schema().instances.lock().await.remove(&node);
+                // Stops its timer and any executions waiting on it
+                self.scheduler.remove_node(graph, node.id);
+
+                CoreNotification::NodeDeleted {
+                    graph,
+                    node: node.id,
+                }
+            }
+            Command::Connect(connection) => {
+                self.connect_io(
+                    graph,
+                    connection.output_node,
+                    &connection.output,
+                    connection.input_node,
+                    &connection.input,
+                )?;
+
+                CoreNotification::IOConnected {
+                    graph,
+                    output_node: connection.output_node,
+                    output: connection.output,
+                    input_node: connection.input_node,
+                    input: connection.input,
+                }
+            }
+            Command::Disconnect(connection) => {
+                self.disconnect_io(graph, connection.input_node, &connection.input, true)?;
+
+                CoreNotification::IODisconnected {
+                    graph,
+                    node: connection.input_node,
+                    io: connection.input,
+                    is_input: true,
+                }
+            }
+            Command::MoveNode { node, to, .. } => {
+                self.try_node(graph, node)?.set_position(to.clone());
+
+                CoreNotification::NodeMoved {
+                    graph,
+                    node,
+                    position: to,
+                }
+            }
+            Command::SetDefaultValue {
+                node, input, to, ..
+            } => {
+                self.try_node(graph, node)?
+                    .find_data_input(&input)
+                    .ok_or(CoreError::UnknownIO {
+                        node,
+                        io: input.clone(),
+                        is_input: true,
+                    })?
+                    .set_default_value(to.clone());
+
+                CoreNotification::DefaultValueSet {
+                    graph,
+                    node,
+                    input,
+                    value: to,
+                }
+            }
+            Command::SetWildcards { node, to, .. } => {
+                let node = self.try_node(graph, node)?;
+                node.set_wildcards(to);
+
+                CoreNotification::WildcardsChanged {
+                    graph,
+                    node: node.as_ref().into(),
+                }
+            }
+            Command::SetProperty {
+                node, property, to, ..
+            } => {
+                let node = self.try_node(graph, node)?;
+                node.reconcile_io(node.build_with_property(&property, to));
+
+                CoreNotification::NodePropertySet {
+                    graph,
+                    node: node.as_ref().into(),
+                }
+            }
+            Command::Rename { to, .. } => {
+                let graph_ref = self.try_graph_mut(graph)?;
+                graph_ref.set_name(to.clone());
+
+                // Function schemas are named after their graph
+                if graph_ref.function.is_some() {
+                    self.update_function_schemas(graph).await;
+                }
+
+                CoreNotification::GraphRenamed {
+                    id: graph,
+                    name: to,
+                }
+            }
+        };
+
+        self.notifier.notify(notification);
+
+        Ok(())
+    }
+
+    pub fn connect_io(
+        &mut self,
+        graph: i32,
+        output_node: i32,
+        output: &str,
         input_node: i32,
         input: &str,
     ) -> Result<(), ConnectIOError> {
-        let output_node = self.graph(graph).unwrap().node(output_node);
-        let input_node = self.graph(graph).unwrap().node(input_node);
+        match self.check_connection(graph, output_node, output, input_node, input)? {
+            (Output::Data(output), Input::Data(input)) => {
+                // Wildcards have to be resolved before they're connected, unless
+                // both are still waiting for a type
+                let pending = output.r#type.is_wildcard() && input.r#type.is_wildcard();
+
+                if !pending && output.r#type != input.r#type {
+                    return Err(ConnectIOError::TypeMismatch {
+                        expected: input.r#type,
+                        found: output.r#type,
+                    });
+                }
+
+                output.connect_input(&input);
+                input.connect_output(&output);
+            }
+            (Output::Exec(output), Input::Exec(input)) => {
+                output.connect_input(&input);
+                input.connect_output(&output);
+            }
+            _ => return Err(ConnectIOError::KindMismatch),
+        };
+
+        Ok(())
+    }
+
+    // Commands that resolve the wildcards of a connection to the type of the
+    // IO on its other side, along with every wildcard connected to them
+    fn resolve_wildcards(output: &Output, input: &Input) -> Transaction {
+        let (output, input) = match (output, input) {
+            (Output::Data(output), Input::Data(input)) => (output, input),
+            _ => return vec![],
+        };
+
+        let (node, wildcard, resolved) = match (output.r#type, input.r#type) {
+            // Both stay unresolved until something with a type is connected
+            (ValueType::Wildcard(_), ValueType::Wildcard(_)) => return vec![],
+            (ValueType::Wildcard(wildcard), resolved) => (&output.node, wildcard, resolved),
+            (resolved, ValueType::Wildcard(wildcard)) => (&input.node, wildcard, resolved),
+            _ => return vec![],
+        };
+
+        let set = match node.upgrade() {
+            Some(node) => node.wildcard_set(wildcard.group),
+            None => return vec![],
+        };
+
+        Self::set_wildcards(update_wildcards([set], |wildcards, group| {
+            wildcards.insert(group, resolved);
+        }))
+    }
+
+    // Commands that turn the nodes' resolved wildcards that nothing but other
+    // wildcards are connected to anymore back into wildcards
+    fn release_wildcards(&self, graph: i32, mut nodes: Vec<i32>) -> Transaction {
+        nodes.sort();
+        nodes.dedup();
+
+        let released = nodes
+            .into_iter()
+            .filter_map(|node| self.graph(graph)?.node(node))
+            .flat_map(|node| node.released_wildcard_sets());
+
+        Self::set_wildcards(update_wildcards(released, |wildcards, group| {
+            wildcards.remove(&group);
+        }))
+    }
+
+    fn set_wildcards(updated: Vec<(Arc<Node>, HashMap<u32, ValueType>)>) -> Transaction {
+        updated
+            .into_iter()
+            .map(|(node, to)| Command::SetWildcards {
+                node: node.id,
+                from: node.wildcards.lock().unwrap().clone(),
+                to,
+            })
+            .collect()
+    }
+
+    // Finds the IO for a connection, checking that they can be connected
+    fn check_connection(
+        &self,
+        graph: i32,
+        output_node: i32,
+        output: &str,
+        input_node: i32,
+        input: &str,
+    ) -> Result<(Output, Input), ConnectIOError> {
+        let graph = self.graph(graph).ok_or(ConnectIOError::InvalidGraph)?;
+
+        let output_node = graph.node(output_node);
+        let input_node = graph.node(input_node);
 
         let (output_node, input_node) = match (output_node, input_node) {
             (Some(output_node), Some(input_node)) => (output_node, input_node),
-            (input, output) => {
+            (output, input) => {
                 return Err(ConnectIOError::InvalidNodes {
                     input: input.is_none(),
                     output: output.is_none(),
@@ -300,6 +1822,10 @@ impl Core {
             }
         };
 
+        if output_node.id == input_node.id {
+            return Err(ConnectIOError::SameNode);
+        }
+
         let (output, input) = match (
             output_node.find_output(output),
             input_node.find_input(input),
@@ -313,19 +1839,41 @@ impl Core {
             }
         };
 
-        match (output, input) {
+        match (&output, &input) {
             (Output::Data(output), Input::Data(input)) => {
-                output.connect_input(&input);
-                input.connect_output(&output);
-            }
-            (Output::Exec(output), Input::Exec(input)) => {
-                output.connect_input(&input);
-                input.connect_output(&output);
+                let matches = match (output.r#type, input.r#type) {
+                    (ValueType::Wildcard(output_wildcard), ValueType::Wildcard(input_wildcard)) => {
+                        let output_set = output_node.wildcard_set(output_wildcard.group);
+                        let input_set = input_node.wildcard_set(input_wildcard.group);
+
+                        output_set.overlaps(&input_set)
+                    }
+                    (ValueType::Wildcard(wildcard), r#type) => {
+                        output_node.wildcard_set(wildcard.group).accepts(&r#type)
+                    }
+                    (r#type, ValueType::Wildcard(wildcard)) => {
+                        input_node.wildcard_set(wildcard.group).accepts(&r#type)
+                    }
+                    (output_type, input_type) => output_type == input_type,
+                };
+
+                if !matches {
+                    return Err(ConnectIOError::TypeMismatch {
+                        expected: input.r#type,
+                        found: output.r#type,
+                    });
+                }
+
+                // Exec connections may loop, but data has to be resolvable
+                if output_node.depends_on(input_node.id) {
+                    return Err(ConnectIOError::WouldCreateCycle);
+                }
             }
-            _ => {}
+            (Output::Exec(_), Input::Exec(_)) => {}
+            _ => return Err(ConnectIOError::KindMismatch),
         };
 
-        Ok(())
+        Ok((output, input))
     }
 
     pub fn disconnect_io(
@@ -334,35 +1882,21 @@ impl Core {
         node: i32,
         io: &str,
         is_input: bool,
-    ) -> Result<(), ()> {
-        let node = self.graph(graph).unwrap().node(node);
+    ) -> Result<(), CoreError> {
+        let node_ref = self.try_node(graph, node)?;
 
-        let node = match node {
-            Some(node) => node,
-            None => return Err(()),
+        let unknown_io = || CoreError::UnknownIO {
+            node,
+            io: io.to_string(),
+            is_input,
         };
 
         match is_input {
-            true => {
-                let input = node.find_input(io);
-
-                match input {
-                    Some(input) => {
-                        input.disconnect();
-                    }
-                    None => return Err(()),
-                }
-            }
-            false => {
-                let output = node.find_output(io);
-
-                match output {
-                    Some(output) => {
-                        output.disconnect();
-                    }
-                    None => return Err(()),
-                }
-            }
+            true => node_ref.find_input(io).ok_or_else(unknown_io)?.disconnect(),
+            false => node_ref
+                .find_output(io)
+                .ok_or_else(unknown_io)?
+                .disconnect(),
         };
 
         Ok(())
@@ -377,112 +1911,604 @@ impl Core {
                 Some(event) = self.event_channel.1.recv() => {
                     self.handle_event(event).await;
                 }
+                Some(variable) = self.variable_changes.recv() => {
+                    self.handle_variable_change(variable).await;
+                }
+                Some(node) = self.timer_fires.recv() => {
+                    self.fire_timer(node);
+                }
+                _ = tick_package_watcher(&mut self.package_watcher) => {
+                    self.reload_changed_packages().await;
+                }
             }
         }
     }
 
-    pub async fn handle_event(&self, event: Event) {
-        let schema = self.package(&event.package).unwrap().schema(&event.event);
+    pub fn event_policy(&self, package: &str, event: &str) -> ConcurrencyPolicy {
+        self.event_policies
+            .get(&(package.to_string(), event.to_string()))
+            .copied()
+            .unwrap_or_default()
+    }
 
-        if let Some(schema) = schema {
-            let nodes = schema.instances.lock().await;
+    pub fn set_event_policy(
+        &mut self,
+        package: &str,
+        event: &str,
+        policy: ConcurrencyPolicy,
+    ) -> Result<(), CoreError> {
+        let schema = self
+            .package(package)
+            .ok_or(CoreError::UnknownPackage {
+                package: package.to_string(),
+            })?
+            .event_schema(event);
+
+        match schema.map(|schema| &****schema) {
+            Some(NodeSchemaType::Event { .. }) => {
+                self.event_policies
+                    .insert((package.to_string(), event.to_string()), policy);
+                Ok(())
+            }
+            _ => Err(CoreError::UnknownSchema {
+                package: package.to_string(),
+                schema: event.to_string(),
+            }),
+        }
+    }
+
+    // Starts an execution for every instance of the event's schema. Executions
+    // run as their own tasks so that the Core can keep processing requests.
+    pub async fn handle_event(&mut self, event: Event) {
+        let schema = match self
+            .package(&event.package)
+            .and_then(|package| package.event_schema(&event.event))
+        {
+            Some(schema) => schema.clone(),
+            None => return,
+        };
+
+        self.notifier.notify(CoreNotification::EventFired {
+            package: event.package.clone(),
+            event: event.event.clone(),
+        });
 
-            let futures: Vec<_> = nodes
+        self.fire_schema(&schema, event.data.into()).await;
+    }
+
+    // Runs the Changed nodes of a variable that was just set
+    async fn handle_variable_change(&mut self, variable: Variable) {
+        self.notifier.notify(CoreNotification::VariableChanged {
+            variable: variable.id,
+            value: variable.value.clone(),
+        });
+
+        let schema = self.package(FUNCTION_PACKAGE).and_then(|package| {
+            package
+                .schemas
                 .iter()
-                .map(|node| self.fire_node(node.graph_id, node.id, &*event.data))
-                .collect();
+                .find(|schema| schema.is_variable(variable.id, VariableAccess::Changed))
+                .cloned()
+        });
 
-            futures::future::join_all(futures).await;
+        if let Some(schema) = schema {
+            self.fire_schema(&schema, Arc::new(variable.value)).await;
         }
     }
 
-    pub(crate) async fn execute_node(&self, node: &Arc<Node>) -> Option<&'static str> {
-        for input in node.inputs.lock().unwrap().iter() {
-            if let Input::Data(input) = input {
-                if let Some(output) = input.connected_output.lock().unwrap().upgrade() {
-                    match ***output.node.upgrade().unwrap().schema {
-                        NodeSchemaType::Exec { .. } | NodeSchemaType::Event { .. } => {
-                            input.set_value(output.value.load_full())
-                        }
-                        _ => {}
-                    };
-                } else {
-                    input.reset_value();
-                }
-            }
+    // Starts an execution from an Every N ms or At Time node
+    fn fire_timer(&mut self, node: Arc<Node>) {
+        // The node may have been removed after the timer fired
+        let current = self.graph(node.graph_id).and_then(|g| g.node(node.id));
+        if !current.is_some_and(|current| Arc::ptr_eq(current, &node)) {
+            return;
         }
 
-        let package = self.package(&node.schema.package).unwrap();
-        let request_send_channel = match &package.engine {
-            Some(Engine::Running { request_sender }) => Some(request_sender.clone()),
-            None => None,
-            _ => {
-                println!("Attempted to executed node before engine ran");
-                return None;
-            }
-        };
+        let schema = node.schema();
+        let policy = self.event_policy(&schema.package, &schema.name);
+        let executor = self.executor();
 
-        let context = ExecuteContext::new(request_send_channel, package.runtime.handle().clone());
+        self.notifier.notify(CoreNotification::EventFired {
+            package: schema.package.clone(),
+            event: schema.name.clone(),
+        });
 
-        let mut io_data = node.get_io_data();
+        self.spawn_execution(&executor, node, Arc::new(()), policy);
+    }
 
-        let res = match &***node.schema {
-            NodeSchemaType::Base { execute } => match execute {
-                ExecuteFn::Sync(execute) => execute(&mut io_data, context),
-                ExecuteFn::Async(execute) => execute(&mut io_data, context).await,
-            },
-            NodeSchemaType::Exec { execute } => {
-                match execute {
-                    ExecuteFn::Sync(execute) => execute(&mut io_data, context),
-                    ExecuteFn::Async(execute) => execute(&mut io_data, context).await,
-                };
-                Some("")
-            }
-            _ => None,
-        };
+    async fn fire_schema(&mut self, schema: &Arc<NodeSchema>, data: Arc<dyn Any + Send + Sync>) {
+        let policy = self.event_policy(&schema.package, &schema.name);
+        let executor = self.executor();
 
-        node.parse_io_data(io_data);
+        let nodes: Vec<_> = schema.instances.lock().await.iter().collect();
 
-        res
+        for node in nodes {
+            self.spawn_execution(&executor, node, data.clone(), policy);
+        }
     }
 
-    pub(crate) async fn fire_node(&self, graph: i32, node_id: i32, data: &(dyn Any + Sync)) {
-        let node = self.graph(graph).unwrap().nodes.get(&node_id).unwrap();
+    fn executor(&self) -> Executor {
+        Executor::new(
+            &self.packages,
+            self.tracer.clone(),
+            self.debugger.clone(),
+            self.notifier.clone(),
+        )
+    }
 
-        let fire = match ***node.schema {
-            NodeSchemaType::Event { fire } => fire,
-            _ => return,
+    fn spawn_execution(
+        &mut self,
+        executor: &Executor,
+        node: Arc<Node>,
+        data: Arc<dyn Any + Send + Sync>,
+        policy: ConcurrencyPolicy,
+    ) {
+        let source = ExecutionSource {
+            graph: node.graph_id,
+            node: node.id,
         };
 
-        let mut io_data = node.get_io_data();
+        let queue = match policy {
+            ConcurrencyPolicy::Parallel => None,
+            ConcurrencyPolicy::Queue => {
+                // Queues are only kept while executions are waiting on them
+                self.event_queues
+                    .retain(|_, queue| Arc::strong_count(queue) > 1);
+
+                Some(self.event_queues.entry(source).or_default().clone())
+            }
+            ConcurrencyPolicy::DropIfRunning => {
+                if self.executions.is_running(source) {
+                    return;
+                }
 
-        let mut target_output_mut =
-            (fire)(&mut io_data, data).and_then(|id| node.find_exec_output(id));
+                None
+            }
+            ConcurrencyPolicy::Restart => {
+                self.executions.cancel_source(source);
+                None
+            }
+        };
 
-        node.parse_io_data(io_data);
+        let mut execution = self.executions.start(self.execution_limits, source);
+        let executor = executor.clone();
 
-        while let Some(target_output) = target_output_mut.as_ref() {
-            let connected_input = {
-                let o = target_output.clone();
-                let i = &*o.connected_input.lock().unwrap();
-                i.clone()
+        tokio::spawn(async move {
+            let _guard = match &queue {
+                Some(queue) => tokio::select! {
+                    guard = queue.lock() => Some(guard),
+                    _ = execution.cancelled() => return,
+                },
+                None => None,
             };
 
-            target_output_mut = if let Some(connected_input) = connected_input.upgrade() {
-                let node = connected_input.node.upgrade().unwrap();
-
-                self.execute_node(&node)
-                    .await
-                    .and_then(|id| node.find_exec_output(id))
-            } else {
-                None
+            if let Err(e) = executor.run(&mut execution, &node, &*data).await {
+                log::warn!(
+                    "Execution from node {} in graph {} stopped: {}",
+                    node.id,
+                    node.graph_id,
+                    e
+                );
             }
-        }
+        });
     }
 
     pub fn get_controller(&self) -> CoreController {
         CoreController {
             request_sender: self.request_channel.0.clone(),
+            executions: self.executions.clone(),
+            tracer: self.tracer.clone(),
+            notifier: self.notifier.clone(),
+        }
+    }
+}
+
+impl Default for Core {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Moves a schema's nodes to its replacement, updating their IO to match
+async fn migrate_instances(old: &NodeSchema, new: &Arc<NodeSchema>) {
+    let nodes: Vec<_> = old.instances.lock().await.iter().collect();
+    let mut instances = new.instances.lock().await;
+
+    for node in nodes {
+        node.schema.store(new.clone());
+
+        // Properties are kept, unless the new schema doesn't accept them
+        node.reconcile_io(node.build(node.property_values()));
+
+        instances.insert(node);
+    }
+}
+
+async fn tick_package_watcher(watcher: &mut Option<PackageWatcher>) {
+    match watcher {
+        Some(watcher) => watcher.tick().await,
+        None => futures::future::pending().await,
+    }
+}
+
+// Checks that parameter names are unique and not empty
+fn check_parameters(parameters: &[FunctionParameter]) -> Result<(), CoreError> {
+    for (index, parameter) in parameters.iter().enumerate() {
+        if parameter.name.is_empty() || parameters[..index].iter().any(|p| p.name == parameter.name)
+        {
+            return Err(CoreError::InvalidParameter {
+                name: parameter.name.clone(),
+            });
+        }
+    }
+
+    Ok(())
+}
+
+// Disconnects everything from the node before removing it, so that undoing the
+// deletion restores its connections
+fn delete_node_transaction(node: &Arc<Node>) -> Transaction {
+    let mut transaction: Transaction = node
+        .input_connections()
+        .into_iter()
+        .chain(node.output_connections())
+        .map(Command::Disconnect)
+        .collect();
+    transaction.push(Command::RemoveNode(node.clone()));
+    transaction
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::executor::block_on;
+    use macrograph_package_api::{
+        schema::ExecuteFn,
+        value::types::{PrimitiveType, WildcardConstraint},
+    };
+
+    use super::*;
+    use crate::{execution::Execution, value::Value};
+
+    fn rename(from: &str, to: &str) -> Command {
+        Command::Rename {
+            from: from.into(),
+            to: to.into(),
+        }
+    }
+
+    fn position() -> Position {
+        serde_json::from_str(r#"{ "x": 0, "y": 0 }"#).unwrap()
+    }
+
+    // Fails to apply, since the graph has no nodes
+    fn move_missing_node() -> Command {
+        Command::MoveNode {
+            node: 99,
+            from: position(),
+            to: position(),
+        }
+    }
+
+    fn graph_name(core: &Core) -> &str {
+        &core.graph(0).unwrap().name
+    }
+
+    #[test]
+    fn failed_transactions_are_rolled_back() {
+        let mut core = Core::new();
+
+        let result =
+            block_on(core.apply(0, vec![rename("Graph 0", "Renamed"), move_missing_node()]));
+
+        assert_eq!(result, Err(CoreError::UnknownNode { graph: 0, node: 99 }));
+        assert_eq!(graph_name(&core), "Graph 0");
+        assert!(core.history(0).next_undo().is_none());
+    }
+
+    #[test]
+    fn undo_and_redo() {
+        let mut core = Core::new();
+
+        block_on(core.handle_request(Request::RenameGraph {
+            id: 0,
+            name: "Renamed".into(),
+        }))
+        .unwrap();
+
+        block_on(core.handle_request(Request::Undo { graph: 0 })).unwrap();
+        assert_eq!(graph_name(&core), "Graph 0");
+
+        block_on(core.handle_request(Request::Redo { graph: 0 })).unwrap();
+        assert_eq!(graph_name(&core), "Renamed");
+
+        assert_eq!(
+            block_on(core.handle_request(Request::Redo { graph: 0 })).err(),
+            Some(CoreError::NothingToRedo { graph: 0 })
+        );
+    }
+
+    #[test]
+    fn failed_undo_discards_history() {
+        let mut core = Core::new();
+
+        // Undoing renames the graph before failing to move the node
+        core.history(0)
+            .record(vec![move_missing_node(), rename("Original", "Graph 0")]);
+
+        assert_eq!(
+            block_on(core.handle_request(Request::Undo { graph: 0 })).err(),
+            Some(CoreError::UnknownNode { graph: 0, node: 99 })
+        );
+        assert_eq!(graph_name(&core), "Graph 0");
+        assert_eq!(
+            block_on(core.handle_request(Request::Undo { graph: 0 })).err(),
+            Some(CoreError::NothingToUndo { graph: 0 })
+        );
+    }
+
+    // Identity passes on a value of any type, so both of its pins take the
+    // type of whatever is connected to either
+    fn test_core() -> Core {
+        let mut package = ApiPackage::new("Test");
+        package.add_pure_schema("Int", |s| s.int_output("Value"), ExecuteFn::Sync(|_, _| {}));
+        package.add_pure_schema(
+            "Ints",
+            |s| s.list_output::<i32>("Value"),
+            ExecuteFn::Sync(|_, _| {}),
+        );
+        package.add_pure_schema(
+            "Identity",
+            |s| {
+                s.wildcard_input("In", 0, WildcardConstraint::Any);
+                s.wildcard_output("Out", 0, WildcardConstraint::Any);
+            },
+            ExecuteFn::Sync(|_, _| {}),
+        );
+        package.add_pure_schema(
+            "Negate",
+            |s| {
+                s.wildcard_input("In", 0, WildcardConstraint::Number);
+                s.wildcard_output("Out", 0, WildcardConstraint::Number);
+            },
+            ExecuteFn::Sync(|_, _| {}),
+        );
+        package.add_exec_schema("Sink", |s| s.int_input("In"), ExecuteFn::Sync(|_, _| {}));
+        package.add_exec_schema(
+            "Length",
+            |s| s.wildcard_input("In", 0, WildcardConstraint::List),
+            ExecuteFn::Sync(|_, _| {}),
+        );
+        package.add_exec_schema(
+            "List Sink",
+            |s| s.list_input::<i32>("In"),
+            ExecuteFn::Sync(|_, _| {}),
+        );
+
+        let mut core = Core::new();
+        core.packages.push(package.into());
+        core
+    }
+
+    fn create_node(core: &mut Core, schema: &str) -> Arc<Node> {
+        let node = block_on(core.handle_request(Request::CreateNode {
+            graph: 0,
+            package: "Test".into(),
+            schema: schema.into(),
+            position: position(),
+        }));
+
+        match node {
+            Ok(Response::CreateNode { id, .. }) => core.graph(0).unwrap().node(id).unwrap().clone(),
+            _ => panic!("Failed to create {}", schema),
+        }
+    }
+
+    fn connect(core: &mut Core, output: (&Arc<Node>, &str), input: (&Arc<Node>, &str)) {
+        block_on(core.handle_request(Request::ConnectIO {
+            graph: 0,
+            output_node: output.0.id,
+            output: output.1.into(),
+            input_node: input.0.id,
+            input: input.1.into(),
+        }))
+        .unwrap();
+    }
+
+    fn disconnect(core: &mut Core, node: &Arc<Node>, input: &str) {
+        block_on(core.handle_request(Request::DisconnectIO {
+            graph: 0,
+            node: node.id,
+            io: input.into(),
+            is_input: true,
+        }))
+        .unwrap();
+    }
+
+    fn output_type(node: &Node) -> ValueType {
+        node.find_data_output("Out").unwrap().r#type
+    }
+
+    const INT: ValueType = ValueType::Primitive(PrimitiveType::Int);
+
+    #[test]
+    fn connecting_resolves_wildcards() {
+        let mut core = test_core();
+        let int = create_node(&mut core, "Int");
+        let identity = create_node(&mut core, "Identity");
+
+        assert!(matches!(output_type(&identity), ValueType::Wildcard(_)));
+
+        connect(&mut core, (&int, "Value"), (&identity, "In"));
+
+        assert_eq!(identity.wildcards.lock().unwrap().get(&0), Some(&INT));
+        assert_eq!(identity.find_data_input("In").unwrap().r#type, INT);
+        assert_eq!(output_type(&identity), INT);
+    }
+
+    #[test]
+    fn disconnecting_releases_wildcards() {
+        let mut core = test_core();
+        let int = create_node(&mut core, "Int");
+        let identity = create_node(&mut core, "Identity");
+
+        connect(&mut core, (&int, "Value"), (&identity, "In"));
+        disconnect(&mut core, &identity, "In");
+
+        assert!(identity.wildcards.lock().unwrap().is_empty());
+        assert!(matches!(output_type(&identity), ValueType::Wildcard(_)));
+    }
+
+    #[test]
+    fn wildcards_stay_resolved_while_connected() {
+        let mut core = test_core();
+        let int = create_node(&mut core, "Int");
+        let identity = create_node(&mut core, "Identity");
+        let sink = create_node(&mut core, "Sink");
+
+        connect(&mut core, (&int, "Value"), (&identity, "In"));
+        connect(&mut core, (&identity, "Out"), (&sink, "In"));
+        disconnect(&mut core, &identity, "In");
+
+        assert_eq!(output_type(&identity), INT);
+
+        disconnect(&mut core, &sink, "In");
+
+        assert!(matches!(output_type(&identity), ValueType::Wildcard(_)));
+    }
+
+    #[test]
+    fn wildcards_connect_before_resolving() {
+        let mut core = test_core();
+        let int = create_node(&mut core, "Int");
+        let first = create_node(&mut core, "Identity");
+        let second = create_node(&mut core, "Identity");
+
+        connect(&mut core, (&first, "Out"), (&second, "In"));
+
+        assert!(matches!(output_type(&second), ValueType::Wildcard(_)));
+
+        connect(&mut core, (&int, "Value"), (&first, "In"));
+
+        assert_eq!(output_type(&first), INT);
+        assert_eq!(output_type(&second), INT);
+        assert!(second
+            .find_input("In")
+            .unwrap()
+            .connected_output()
+            .is_some());
+    }
+
+    #[test]
+    fn connected_wildcards_are_released_together() {
+        let mut core = test_core();
+        let int = create_node(&mut core, "Int");
+        let first = create_node(&mut core, "Identity");
+        let second = create_node(&mut core, "Identity");
+
+        connect(&mut core, (&first, "Out"), (&second, "In"));
+        connect(&mut core, (&int, "Value"), (&first, "In"));
+        disconnect(&mut core, &first, "In");
+
+        assert!(matches!(output_type(&first), ValueType::Wildcard(_)));
+        assert!(matches!(output_type(&second), ValueType::Wildcard(_)));
+        assert!(second
+            .find_input("In")
+            .unwrap()
+            .connected_output()
+            .is_some());
+    }
+
+    #[test]
+    fn wildcards_with_incompatible_constraints_do_not_connect() {
+        let mut core = test_core();
+        let negate = create_node(&mut core, "Negate");
+        let length = create_node(&mut core, "Length");
+
+        let result = block_on(core.handle_request(Request::ConnectIO {
+            graph: 0,
+            output_node: negate.id,
+            output: "Out".into(),
+            input_node: length.id,
+            input: "In".into(),
+        }));
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn undoing_restores_wildcards() {
+        let mut core = test_core();
+        let int = create_node(&mut core, "Int");
+        let identity = create_node(&mut core, "Identity");
+
+        connect(&mut core, (&int, "Value"), (&identity, "In"));
+        disconnect(&mut core, &identity, "In");
+
+        block_on(core.handle_request(Request::Undo { graph: 0 })).unwrap();
+
+        assert_eq!(output_type(&identity), INT);
+        assert!(identity
+            .find_input("In")
+            .unwrap()
+            .connected_output()
+            .is_some());
+
+        block_on(core.handle_request(Request::Undo { graph: 0 })).unwrap();
+
+        assert!(matches!(output_type(&identity), ValueType::Wildcard(_)));
+    }
+
+    #[test]
+    fn disconnected_list_inputs_are_reset() {
+        let mut core = test_core();
+        let ints = create_node(&mut core, "Ints");
+        let sink = create_node(&mut core, "List Sink");
+
+        connect(&mut core, (&ints, "Value"), (&sink, "In"));
+
+        // As if an execution had pulled a value from the output
+        let input = sink.find_data_input("In").unwrap();
+        input.set_value(Arc::new(Value::List(vec![1, 2, 3].into())));
+
+        disconnect(&mut core, &sink, "In");
+
+        // Executions reset inputs that aren't connected when pulling them
+        input.reset_value();
+
+        let list = input.get_value().as_list::<i32>().unwrap();
+        assert!(list.values.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn executions_keep_their_own_values() {
+        let mut core = test_core();
+        let int = create_node(&mut core, "Int");
+        let sink = create_node(&mut core, "Sink");
+
+        connect(&mut core, (&int, "Value"), (&sink, "In"));
+
+        let source = ExecutionSource {
+            graph: 0,
+            node: int.id,
+        };
+        let mut first = core.executions.start(Default::default(), source);
+        let mut second = core.executions.start(Default::default(), source);
+
+        let output = int.find_data_output("Value").unwrap();
+        first.set_output_value(&int, &output, Arc::new(Value::from(1)));
+        second.set_output_value(&int, &output, Arc::new(Value::from(2)));
+
+        let value = |execution: &Execution| execution.io_data(&sink).inputs["In"].as_int();
+        let input = sink.find_data_input("In").unwrap();
+
+        for (execution, expected) in [(&mut first, 1), (&mut second, 2)] {
+            let pulled = execution.output_value(&int, &output);
+            execution.set_input_value(&sink, &input, pulled);
+            assert_eq!(value(execution), Some(expected));
         }
+
+        assert_eq!(value(&first), Some(1));
+        // The node keeps the last value given to it, for inspecting
+        assert_eq!(output.value.load().as_int(), Some(2));
     }
 }
@@ -0,0 +1,119 @@
+use std::{collections::HashMap, sync::Arc};
+
+use macrograph_package_api::{
+    engine::Event,
+    schema::{ExecuteFn, NodeSchema as NodeSchemaInner},
+    BuildSchema, Value,
+};
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc::UnboundedSender;
+use ts_rs::TS;
+
+use crate::{
+    execution::Execution,
+    function::{FunctionParameter, FUNCTION_PACKAGE},
+    node::Node,
+    schema::{CoreSchema, NodeSchema},
+};
+
+// An event defined by the project rather than a package, which any graph can
+// call and handle
+#[derive(TS, Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[ts(export)]
+pub struct CustomEvent {
+    pub name: String,
+    // Inputs of the nodes that call the event and outputs of the nodes it fires
+    pub payload: Vec<FunctionParameter>,
+}
+
+// The event data sent by Call nodes, keyed by parameter name
+pub(crate) struct CustomEventPayload(pub HashMap<String, Value>);
+
+#[derive(Clone)]
+pub(crate) enum CustomEventSchema {
+    // Fired with the payload whenever the event is called
+    Define(CustomEvent),
+    // Sends the event to the Core, which fires it like any package event
+    Call(CustomEvent, UnboundedSender<Event>),
+}
+
+impl CustomEventSchema {
+    pub fn event(&self) -> &CustomEvent {
+        match self {
+            Self::Define(event) | Self::Call(event, _) => event,
+        }
+    }
+
+    pub fn build(&self, schema: &mut BuildSchema) {
+        match self {
+            Self::Define(event) => {
+                schema.exec_output("");
+
+                for parameter in &event.payload {
+                    schema.data_output(&parameter.name, parameter.r#type);
+                }
+            }
+            Self::Call(event, _) => {
+                schema.exec_input("");
+                schema.exec_output("");
+
+                for parameter in &event.payload {
+                    schema.data_input(&parameter.name, parameter.r#type);
+                }
+            }
+        }
+    }
+
+    // Only Call nodes are executed, since Define nodes are events
+    pub fn execute(&self, execution: &Execution, node: &Node) -> Option<&'static str> {
+        if let Self::Call(event, sender) = self {
+            let payload = CustomEventPayload(execution.io_data(node).inputs);
+
+            // The Core may have been dropped, in which case nobody is listening
+            sender
+                .send(Event::new(FUNCTION_PACKAGE, &event.name, payload))
+                .ok();
+        }
+
+        Some("")
+    }
+}
+
+// Creates the schemas for a custom event: the event itself, followed by the
+// node that calls it, which sends the event with sender
+pub(crate) fn custom_event_schemas(
+    event: &CustomEvent,
+    sender: &UnboundedSender<Event>,
+) -> [Arc<NodeSchema>; 2] {
+    let mut define = NodeSchemaInner::new_event(
+        &event.name,
+        |_| {},
+        |io, data| {
+            if let Some(payload) = data.downcast_ref::<CustomEventPayload>() {
+                io.outputs.extend(payload.0.clone());
+            }
+
+            Some("")
+        },
+    );
+    // Never run, since the CustomEventSchema sends the event itself
+    let mut call = NodeSchemaInner::new_base(
+        &format!("Call {}", event.name),
+        |_| {},
+        ExecuteFn::Sync(|_, _| None),
+    );
+
+    define.package = FUNCTION_PACKAGE.to_string();
+    call.package = FUNCTION_PACKAGE.to_string();
+
+    [
+        Arc::new(NodeSchema::new_core(
+            define,
+            CoreSchema::CustomEvent(CustomEventSchema::Define(event.clone())),
+        )),
+        Arc::new(NodeSchema::new_core(
+            call,
+            CoreSchema::CustomEvent(CustomEventSchema::Call(event.clone(), sender.clone())),
+        )),
+    ]
+}
@@ -0,0 +1,95 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use macrograph_package_api::Value;
+use serde::Serialize;
+use tokio::sync::oneshot;
+use ts_rs::TS;
+
+use crate::{error::CoreError, execution::ExecutionId};
+
+#[derive(TS, Serialize, Debug, Clone)]
+#[ts(export)]
+pub struct PausedExecution {
+    pub execution: ExecutionId,
+    pub graph: i32,
+    pub node: i32,
+    pub inputs: HashMap<String, Value>,
+    pub outputs: HashMap<String, Value>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Resume {
+    Continue,
+    // Pause again before the next node the execution enters
+    StepOver,
+}
+
+type PausedExecutions = HashMap<ExecutionId, (PausedExecution, oneshot::Sender<Resume>)>;
+
+// Executions that are paused at a breakpoint, waiting to be resumed
+#[derive(Clone, Default)]
+pub struct Debugger {
+    paused: Arc<Mutex<PausedExecutions>>,
+}
+
+impl Debugger {
+    pub(crate) fn pause(&self, paused: PausedExecution) -> (PauseGuard, oneshot::Receiver<Resume>) {
+        let (sender, receiver) = oneshot::channel();
+        let execution = paused.execution;
+
+        self.paused
+            .lock()
+            .unwrap()
+            .insert(execution, (paused, sender));
+
+        (
+            PauseGuard {
+                execution,
+                debugger: self.clone(),
+            },
+            receiver,
+        )
+    }
+
+    pub fn paused(&self) -> Vec<PausedExecution> {
+        let mut paused: Vec<_> = self
+            .paused
+            .lock()
+            .unwrap()
+            .values()
+            .map(|(paused, _)| paused.clone())
+            .collect();
+
+        paused.sort_by_key(|paused| paused.execution);
+
+        paused
+    }
+
+    pub(crate) fn resume(&self, execution: ExecutionId, resume: Resume) -> Result<(), CoreError> {
+        let (_, sender) = self
+            .paused
+            .lock()
+            .unwrap()
+            .remove(&execution)
+            .ok_or(CoreError::NotPaused { execution })?;
+
+        sender.send(resume).ok();
+
+        Ok(())
+    }
+}
+
+// Removes the paused execution if it's cancelled before being resumed
+pub(crate) struct PauseGuard {
+    execution: ExecutionId,
+    debugger: Debugger,
+}
+
+impl Drop for PauseGuard {
+    fn drop(&mut self) {
+        self.debugger.paused.lock().unwrap().remove(&self.execution);
+    }
+}
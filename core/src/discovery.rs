@@ -0,0 +1,298 @@
+use std::{
+    collections::HashMap,
+    env::consts::{DLL_EXTENSION, DLL_PREFIX},
+    fs,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+    time::{Duration, SystemTime},
+};
+
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use tokio::time::{interval, Interval, MissedTickBehavior};
+
+use crate::package::{LoadPackageError, Package};
+
+pub const MANIFEST_FILE: &str = "macrograph-package.toml";
+
+#[derive(TS, Serialize, Deserialize, Debug, Clone)]
+#[ts(export)]
+pub struct PackageManifest {
+    pub name: String,
+    pub version: String,
+    pub description: Option<String>,
+    pub author: Option<String>,
+    pub min_core_version: Option<String>,
+    #[serde(default)]
+    pub dependencies: Vec<String>,
+    // Library file relative to the manifest, used when the package's folder
+    // contains more than one library
+    pub library: Option<String>,
+}
+
+impl PackageManifest {
+    pub fn read(path: &Path) -> Result<Self, LoadPackageError> {
+        let contents = fs::read_to_string(path).map_err(LoadPackageError::Io)?;
+
+        toml::from_str(&contents).map_err(|e| LoadPackageError::Manifest(e.to_string()))
+    }
+
+    pub fn check_core_version(&self) -> Result<(), LoadPackageError> {
+        let required = match &self.min_core_version {
+            Some(required) => required,
+            None => return Ok(()),
+        };
+
+        let core = semver::Version::parse(env!("CARGO_PKG_VERSION")).unwrap();
+        let required_version = semver::Version::parse(required)
+            .map_err(|_| LoadPackageError::InvalidVersion(required.clone()))?;
+
+        if core < required_version {
+            return Err(LoadPackageError::IncompatibleCore {
+                required: required.clone(),
+                core: core.to_string(),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+// A directory to look for packages in
+#[derive(Debug, Clone)]
+pub enum PackageDir {
+    // Libraries in the directory, and folders containing a library
+    Packages(PathBuf),
+    // Only libraries directly in the directory. Used for the executable's
+    // directory, whose folders hold build artifacts rather than packages.
+    Libraries(PathBuf),
+}
+
+impl PackageDir {
+    pub fn path(&self) -> &Path {
+        match self {
+            Self::Packages(path) | Self::Libraries(path) => path,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct PackageCandidate {
+    pub library: PathBuf,
+    pub manifest: Option<PackageManifest>,
+}
+
+#[derive(Debug)]
+pub struct PackageLoadFailure {
+    pub path: PathBuf,
+    pub error: LoadPackageError,
+}
+
+fn is_package_library(path: &Path) -> bool {
+    let prefix = format!("{}mg_pkg_", DLL_PREFIX);
+
+    path.extension().is_some_and(|ext| ext == DLL_EXTENSION)
+        && path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.starts_with(&prefix))
+}
+
+fn sorted_entries(dir: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let mut entries = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .collect::<Vec<_>>();
+
+    entries.sort();
+
+    Ok(entries)
+}
+
+fn discover_package_folder(
+    dir: &Path,
+    candidates: &mut Vec<PackageCandidate>,
+    failures: &mut Vec<PackageLoadFailure>,
+) {
+    let manifest_path = dir.join(MANIFEST_FILE);
+
+    if !manifest_path.is_file() {
+        if let Ok(entries) = sorted_entries(dir) {
+            candidates.extend(
+                entries
+                    .into_iter()
+                    .filter(|path| is_package_library(path))
+                    .map(|library| PackageCandidate {
+                        library,
+                        manifest: None,
+                    }),
+            );
+        }
+
+        return;
+    }
+
+    let manifest = match PackageManifest::read(&manifest_path) {
+        Ok(manifest) => manifest,
+        Err(error) => {
+            return failures.push(PackageLoadFailure {
+                path: manifest_path,
+                error,
+            })
+        }
+    };
+
+    let library = match &manifest.library {
+        Some(library) => Some(dir.join(library)),
+        None => sorted_entries(dir).ok().and_then(|entries| {
+            entries
+                .into_iter()
+                .find(|path| path.extension().is_some_and(|ext| ext == DLL_EXTENSION))
+        }),
+    };
+
+    match library {
+        Some(library) => candidates.push(PackageCandidate {
+            library,
+            manifest: Some(manifest),
+        }),
+        None => failures.push(PackageLoadFailure {
+            path: manifest_path,
+            error: LoadPackageError::MissingLibrary,
+        }),
+    }
+}
+
+// Packages built alongside an executable sit next to it, and additional
+// packages can be dropped into a packages folder next to it or any folder
+// listed in MACROGRAPH_PACKAGE_PATH
+pub fn default_package_dirs() -> Vec<PackageDir> {
+    let mut dirs = vec![];
+
+    if let Ok(mut exe_dir) = std::env::current_exe() {
+        exe_dir.pop();
+
+        let user_package_dir = exe_dir.join("packages");
+        dirs.push(PackageDir::Libraries(exe_dir));
+
+        if user_package_dir.is_dir() {
+            dirs.push(PackageDir::Packages(user_package_dir));
+        }
+    }
+
+    if let Some(paths) = std::env::var_os("MACROGRAPH_PACKAGE_PATH") {
+        dirs.extend(std::env::split_paths(&paths).map(PackageDir::Packages));
+    }
+
+    dirs
+}
+
+// Packages are either libraries named mg_pkg_* placed directly in a directory,
+// or folders containing a library and an optional macrograph-package.toml
+pub fn discover_packages(dirs: &[PackageDir]) -> (Vec<PackageCandidate>, Vec<PackageLoadFailure>) {
+    let mut candidates = vec![];
+    let mut failures = vec![];
+
+    for package_dir in dirs {
+        let dir = package_dir.path();
+
+        let entries = match sorted_entries(dir) {
+            Ok(entries) => entries,
+            Err(error) => {
+                failures.push(PackageLoadFailure {
+                    path: dir.to_path_buf(),
+                    error: LoadPackageError::Io(error),
+                });
+                continue;
+            }
+        };
+
+        for path in entries {
+            if path.is_dir() {
+                if let PackageDir::Libraries(_) = package_dir {
+                    continue;
+                }
+
+                discover_package_folder(&path, &mut candidates, &mut failures);
+            } else if is_package_library(&path) {
+                candidates.push(PackageCandidate {
+                    library: path,
+                    manifest: None,
+                });
+            }
+        }
+    }
+
+    (candidates, failures)
+}
+
+// Libraries are loaded from a copy since dlopen returns the already loaded
+// library for a path it has seen before, and Windows locks loaded libraries
+// so they can't be rebuilt
+pub(crate) fn shadow_copy(library: &Path) -> Result<PathBuf, LoadPackageError> {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    let stem = library
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or("package");
+
+    let shadow = std::env::temp_dir().join(format!(
+        "{}-{}-{}.{}",
+        stem,
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed),
+        DLL_EXTENSION
+    ));
+
+    fs::copy(library, &shadow).map_err(LoadPackageError::Io)?;
+
+    Ok(shadow)
+}
+
+// Polls the libraries of loaded packages for changes
+pub struct PackageWatcher {
+    interval: Interval,
+    modified: HashMap<String, SystemTime>,
+}
+
+impl PackageWatcher {
+    pub fn new(period: Duration) -> Self {
+        let mut interval = interval(period);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        Self {
+            interval,
+            modified: HashMap::new(),
+        }
+    }
+
+    pub async fn tick(&mut self) {
+        self.interval.tick().await;
+    }
+
+    // Returns the packages whose libraries were modified since the last poll.
+    // Packages seen for the first time are only recorded.
+    pub fn changed(&mut self, packages: &[Package]) -> Vec<String> {
+        let mut changed = vec![];
+
+        for package in packages {
+            let modified = match package
+                .path
+                .as_ref()
+                .and_then(|path| fs::metadata(path).and_then(|m| m.modified()).ok())
+            {
+                Some(modified) => modified,
+                None => continue,
+            };
+
+            if let Some(previous) = self.modified.insert(package.name.clone(), modified) {
+                if previous != modified {
+                    changed.push(package.name.clone());
+                }
+            }
+        }
+
+        changed
+    }
+}
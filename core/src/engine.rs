@@ -0,0 +1,182 @@
+use std::{
+    any::Any,
+    panic::{self, AssertUnwindSafe},
+    sync::{
+        mpsc::{Receiver, RecvTimeoutError},
+        Arc, Mutex,
+    },
+    thread::JoinHandle,
+    time::{Duration, Instant},
+};
+
+use arc_swap::ArcSwap;
+use libloading::Library;
+use macrograph_package_api::{
+    engine::{EngineContext, Event, RunFn, StateFn},
+    EngineRequest,
+};
+use serde::Serialize;
+use tokio::{
+    runtime::Handle,
+    sync::{
+        mpsc::{unbounded_channel, UnboundedSender},
+        watch,
+    },
+};
+use ts_rs::TS;
+
+use crate::notification::{CoreNotification, Notifier};
+
+#[derive(TS, Serialize, Debug, Clone, PartialEq)]
+#[ts(export)]
+#[serde(tag = "type", content = "data")]
+pub enum EngineStatus {
+    Running,
+    Crashed { message: String },
+    Restarting,
+    Stopped,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct RestartPolicy {
+    pub enabled: bool,
+    pub max_attempts: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl RestartPolicy {
+    fn backoff(&self, attempt: u32) -> Duration {
+        self.initial_backoff
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_backoff)
+    }
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_attempts: 5,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+        }
+    }
+}
+
+// Runs an engine on its own thread, catching panics so that a crashing engine
+// only affects its own package. New request and stop channels are created for
+// every run since the previous receivers are dropped along with the crashed engine.
+// The supervisor exits once the Engine holding restart_receiver's sender is dropped.
+pub(crate) struct EngineSupervisor {
+    pub run: RunFn,
+    pub state: Option<StateFn>,
+    pub package: String,
+    pub handle: Handle,
+    pub event_sender: UnboundedSender<Event>,
+    pub request_sender: Arc<ArcSwap<UnboundedSender<EngineRequest>>>,
+    pub stop_sender: Arc<ArcSwap<watch::Sender<bool>>>,
+    pub status: Arc<Mutex<EngineStatus>>,
+    pub notifier: Notifier,
+    pub restart_receiver: Receiver<()>,
+    pub policy: RestartPolicy,
+    // The engine's code lives in the library, so it can't be unloaded while running
+    pub _library: Option<Arc<Library>>,
+}
+
+impl EngineSupervisor {
+    pub fn spawn(self) -> JoinHandle<()> {
+        std::thread::spawn(move || self.supervise())
+    }
+
+    fn set_status(&self, status: EngineStatus) {
+        *self.status.lock().unwrap() = status.clone();
+
+        self.notifier.notify(CoreNotification::EngineStatusChanged {
+            package: self.package.clone(),
+            status,
+        });
+    }
+
+    fn supervise(self) {
+        let mut attempts = 0;
+
+        loop {
+            let (request_sender, request_receiver) = unbounded_channel();
+            self.request_sender.store(Arc::new(request_sender));
+            let (stop_sender, stop_receiver) = watch::channel(false);
+            self.stop_sender.store(Arc::new(stop_sender));
+
+            // Each run gets fresh state, since the previous run's went with it
+            let ctx = EngineContext {
+                initial_state: self.state.map(|state| state()),
+                request_receiver,
+                handle: self.handle.clone(),
+                package: self.package.clone(),
+                event_sender: self.event_sender.clone(),
+                stop_receiver,
+            };
+
+            self.set_status(EngineStatus::Running);
+
+            let run = self.run;
+            let started = Instant::now();
+            let result = panic::catch_unwind(AssertUnwindSafe(|| run(ctx)));
+
+            let backoff = match result {
+                Ok(()) => {
+                    self.set_status(EngineStatus::Stopped);
+                    None
+                }
+                Err(panic) => {
+                    let message = panic_message(&panic);
+                    log::error!("Engine for package {} crashed: {}", self.package, message);
+
+                    if started.elapsed() > self.policy.max_backoff {
+                        attempts = 0;
+                    }
+
+                    if self.policy.enabled && attempts < self.policy.max_attempts {
+                        let backoff = self.policy.backoff(attempts);
+                        attempts += 1;
+                        self.set_status(EngineStatus::Restarting);
+                        Some(backoff)
+                    } else {
+                        self.set_status(EngineStatus::Crashed { message });
+                        None
+                    }
+                }
+            };
+
+            // A manual restart request skips any remaining backoff
+            let restart = match backoff {
+                Some(backoff) => match self.restart_receiver.recv_timeout(backoff) {
+                    Ok(()) => {
+                        attempts = 0;
+                        true
+                    }
+                    Err(RecvTimeoutError::Timeout) => true,
+                    Err(RecvTimeoutError::Disconnected) => false,
+                },
+                None => {
+                    attempts = 0;
+                    self.restart_receiver.recv().is_ok()
+                }
+            };
+
+            if !restart {
+                break;
+            }
+        }
+    }
+}
+
+fn panic_message(panic: &Box<dyn Any + Send>) -> String {
+    if let Some(message) = panic.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = panic.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "Unknown panic".to_string()
+    }
+}
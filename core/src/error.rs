@@ -0,0 +1,192 @@
+use macrograph_package_api::{primitive::Primitive, value::types::ValueType};
+use serde::Serialize;
+use ts_rs::TS;
+
+use crate::{
+    core::ConnectIOError, execution::ExecutionId, project::ProjectError, variable::VariableId,
+};
+
+#[derive(TS, Serialize, Debug, PartialEq)]
+#[ts(export)]
+#[serde(tag = "type", content = "data")]
+pub enum CoreError {
+    UnknownGraph {
+        graph: i32,
+    },
+    UnknownNode {
+        graph: i32,
+        node: i32,
+    },
+    UnknownPackage {
+        package: String,
+    },
+    UnknownSchema {
+        package: String,
+        schema: String,
+    },
+    UnknownIO {
+        node: i32,
+        io: String,
+        is_input: bool,
+    },
+    InvalidValue {
+        expected: ValueType,
+        found: ValueType,
+    },
+    ConnectIO(ConnectIOError),
+    Project {
+        message: String,
+    },
+    ProjectPathNotAllowed {
+        path: String,
+    },
+    NoEngine {
+        package: String,
+    },
+    EngineRunning {
+        package: String,
+    },
+    NotReloadable {
+        package: String,
+    },
+    LoadPackage {
+        package: String,
+        message: String,
+    },
+    UnknownExecution {
+        execution: ExecutionId,
+    },
+    NotPaused {
+        execution: ExecutionId,
+    },
+    NothingToUndo {
+        graph: i32,
+    },
+    NothingToRedo {
+        graph: i32,
+    },
+    InvalidParameter {
+        name: String,
+    },
+    UnknownVariable {
+        variable: VariableId,
+    },
+    VariableInUse {
+        variable: VariableId,
+        graph: i32,
+        node: i32,
+    },
+    InvalidVariable {
+        name: String,
+    },
+    InvalidCustomEvent {
+        name: String,
+    },
+    UnknownProperty {
+        node: i32,
+        property: String,
+    },
+    InvalidProperty {
+        property: String,
+        value: Primitive,
+    },
+}
+
+impl std::fmt::Display for CoreError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::UnknownGraph { graph } => write!(f, "Graph {} does not exist", graph),
+            Self::UnknownNode { graph, node } => {
+                write!(f, "Node {} does not exist in graph {}", node, graph)
+            }
+            Self::UnknownPackage { package } => write!(f, "Package '{}' is not loaded", package),
+            Self::UnknownSchema { package, schema } => {
+                write!(f, "Package '{}' has no schema '{}'", package, schema)
+            }
+            Self::UnknownIO { node, io, is_input } => write!(
+                f,
+                "Node {} has no {} '{}'",
+                node,
+                if *is_input { "input" } else { "output" },
+                io
+            ),
+            Self::InvalidValue { expected, found } => {
+                write!(
+                    f,
+                    "Expected value of type {:?}, found {:?}",
+                    expected, found
+                )
+            }
+            Self::ConnectIO(e) => write!(f, "Failed to connect IO: {:?}", e),
+            Self::Project { message } => write!(f, "{}", message),
+            Self::ProjectPathNotAllowed { path } => {
+                write!(f, "Projects can't be saved or loaded at '{}'", path)
+            }
+            Self::NoEngine { package } => write!(f, "Package '{}' has no engine", package),
+            Self::EngineRunning { package } => {
+                write!(f, "Engine for package '{}' is already running", package)
+            }
+            Self::NotReloadable { package } => {
+                write!(f, "Package '{}' was not loaded from a library", package)
+            }
+            Self::LoadPackage { package, message } => {
+                write!(f, "Failed to load package '{}': {}", package, message)
+            }
+            Self::UnknownExecution { execution } => {
+                write!(f, "Execution {} is not running", execution)
+            }
+            Self::NotPaused { execution } => {
+                write!(f, "Execution {} is not paused", execution)
+            }
+            Self::NothingToUndo { graph } => write!(f, "Graph {} has nothing to undo", graph),
+            Self::NothingToRedo { graph } => write!(f, "Graph {} has nothing to redo", graph),
+            Self::InvalidParameter { name } => write!(
+                f,
+                "Function parameter names must be unique and not empty, found '{}'",
+                name
+            ),
+            Self::UnknownVariable { variable } => {
+                write!(f, "Variable {} does not exist", variable)
+            }
+            Self::VariableInUse {
+                variable,
+                graph,
+                node,
+            } => write!(
+                f,
+                "Variable {} is used by node {} in graph {}",
+                variable, node, graph
+            ),
+            Self::InvalidVariable { name } => write!(
+                f,
+                "Variable names must be unique within their scope and not empty, found '{}'",
+                name
+            ),
+            Self::InvalidCustomEvent { name } => write!(
+                f,
+                "Custom event names must not be used by other nodes or be empty, found '{}'",
+                name
+            ),
+            Self::UnknownProperty { node, property } => {
+                write!(f, "Node {} has no property '{}'", node, property)
+            }
+            Self::InvalidProperty { property, value } => {
+                write!(f, "Property '{}' can't be set to '{}'", property, value)
+            }
+        }
+    }
+}
+
+impl From<ConnectIOError> for CoreError {
+    fn from(e: ConnectIOError) -> Self {
+        Self::ConnectIO(e)
+    }
+}
+
+impl From<ProjectError> for CoreError {
+    fn from(e: ProjectError) -> Self {
+        Self::Project {
+            message: e.to_string(),
+        }
+    }
+}
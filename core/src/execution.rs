@@ -0,0 +1,492 @@
+use std::{
+    collections::{HashMap, HashSet},
+    future::Future,
+    sync::{Arc, Mutex},
+};
+
+use macrograph_package_api::IOProxy;
+use serde::{Deserialize, Serialize};
+use tokio::sync::watch;
+use ts_rs::TS;
+
+use crate::{
+    error::CoreError,
+    io::{DataInput, DataOutput, Input, Output},
+    node::Node,
+    value::Value,
+};
+
+pub type ExecutionId = u32;
+
+#[derive(Debug, Clone, Copy)]
+pub struct ExecutionLimits {
+    // Maximum number of exec steps a single execution may take
+    pub max_steps: usize,
+    // Stops executions that enter the same exec input twice
+    pub detect_cycles: bool,
+    // Maximum number of function calls an execution may be inside of at once,
+    // since each call nests the future of the one calling it
+    pub max_call_depth: usize,
+}
+
+impl Default for ExecutionLimits {
+    fn default() -> Self {
+        Self {
+            max_steps: 10_000,
+            detect_cycles: true,
+            max_call_depth: 64,
+        }
+    }
+}
+
+// What happens when an event fires while an execution started by the same
+// event node is still running. Executions keep their own values for the IO of
+// the nodes they run, but share the state nodes keep between executions, so
+// Queue is the default to keep executions of the same event from interleaving.
+#[derive(TS, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[ts(export)]
+pub enum ConcurrencyPolicy {
+    Parallel,
+    #[default]
+    Queue,
+    DropIfRunning,
+    Restart,
+}
+
+// The event node an execution was started from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ExecutionSource {
+    pub graph: i32,
+    pub node: i32,
+}
+
+#[derive(TS, Serialize, Debug, Clone, PartialEq)]
+#[ts(export)]
+#[serde(tag = "type", content = "data")]
+pub enum ExecutionError {
+    StepLimit { limit: usize },
+    Cycle { node: i32, input: String },
+    CallDepth { limit: usize },
+    Cancelled,
+}
+
+impl std::fmt::Display for ExecutionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::StepLimit { limit } => write!(f, "Exceeded the limit of {} steps", limit),
+            Self::Cycle { node, input } => {
+                write!(f, "Entered input '{}' of node {} twice", input, node)
+            }
+            Self::CallDepth { limit } => {
+                write!(f, "Exceeded the limit of {} nested function calls", limit)
+            }
+            Self::Cancelled => write!(f, "Cancelled"),
+        }
+    }
+}
+
+struct RunningExecution {
+    source: ExecutionSource,
+    cancel_sender: watch::Sender<bool>,
+}
+
+#[derive(Default)]
+struct ExecutionsInner {
+    id_counter: ExecutionId,
+    running: HashMap<ExecutionId, RunningExecution>,
+}
+
+// Executions that are currently running, shared with CoreController so that
+// they can be cancelled without waiting on the core
+#[derive(Clone, Default)]
+pub struct Executions {
+    inner: Arc<Mutex<ExecutionsInner>>,
+}
+
+impl Executions {
+    pub(crate) fn start(&self, limits: ExecutionLimits, source: ExecutionSource) -> Execution {
+        let mut inner = self.inner.lock().unwrap();
+
+        let id = inner.id_counter;
+        inner.id_counter = inner.id_counter.wrapping_add(1);
+
+        let (cancel_sender, cancel_receiver) = watch::channel(false);
+        inner.running.insert(
+            id,
+            RunningExecution {
+                source,
+                cancel_sender,
+            },
+        );
+
+        Execution {
+            id,
+            limits,
+            steps: 0,
+            call_depth: 0,
+            visited: HashSet::new(),
+            step_over: false,
+            inputs: HashMap::new(),
+            outputs: HashMap::new(),
+            cancel_receiver,
+            executions: self.clone(),
+        }
+    }
+
+    // Cancels a single execution, or all running executions if None
+    pub fn cancel(&self, execution: Option<ExecutionId>) -> Result<(), CoreError> {
+        let inner = self.inner.lock().unwrap();
+
+        match execution {
+            Some(execution) => {
+                inner
+                    .running
+                    .get(&execution)
+                    .ok_or(CoreError::UnknownExecution { execution })?
+                    .cancel_sender
+                    .send(true)
+                    .ok();
+            }
+            None => inner.running.values().for_each(|execution| {
+                execution.cancel_sender.send(true).ok();
+            }),
+        }
+
+        Ok(())
+    }
+
+    pub fn is_running(&self, source: ExecutionSource) -> bool {
+        self.inner
+            .lock()
+            .unwrap()
+            .running
+            .values()
+            .any(|execution| execution.source == source)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inner.lock().unwrap().running.is_empty()
+    }
+
+    pub fn cancel_source(&self, source: ExecutionSource) {
+        self.inner
+            .lock()
+            .unwrap()
+            .running
+            .values()
+            .filter(|execution| execution.source == source)
+            .for_each(|execution| {
+                execution.cancel_sender.send(true).ok();
+            });
+    }
+}
+
+pub(crate) struct Execution {
+    pub id: ExecutionId,
+    limits: ExecutionLimits,
+    steps: usize,
+    call_depth: usize,
+    visited: HashSet<(i32, String)>,
+    // Set when resumed with StepOver, pausing before the next node
+    pub step_over: bool,
+    // The values the execution gave data IO, by graph, node and name
+    inputs: HashMap<(i32, i32, String), Arc<Value>>,
+    outputs: HashMap<(i32, i32, String), Arc<Value>>,
+    cancel_receiver: watch::Receiver<bool>,
+    executions: Executions,
+}
+
+impl Execution {
+    // Counts a step towards the execution's limit, stopping it if cancelled.
+    // Flow nodes call this each time they loop, since looping doesn't enter
+    // an exec input.
+    pub fn tick(&mut self) -> Result<(), ExecutionError> {
+        if *self.cancel_receiver.borrow() {
+            return Err(ExecutionError::Cancelled);
+        }
+
+        self.steps += 1;
+
+        if self.steps > self.limits.max_steps {
+            return Err(ExecutionError::StepLimit {
+                limit: self.limits.max_steps,
+            });
+        }
+
+        Ok(())
+    }
+
+    // Called before the execution enters a node's exec input
+    pub fn step(&mut self, node: i32, input: &str) -> Result<(), ExecutionError> {
+        self.tick()?;
+
+        if self.limits.detect_cycles && !self.visited.insert((node, input.to_string())) {
+            return Err(ExecutionError::Cycle {
+                node,
+                input: input.to_string(),
+            });
+        }
+
+        Ok(())
+    }
+
+    // Function calls and the branches of Flow nodes get their own set of
+    // visited inputs, since calling the same function or running a loop's body
+    // twice isn't a cycle. Recursion is caught by max_call_depth instead.
+    pub fn enter_frame(&mut self) -> HashSet<(i32, String)> {
+        std::mem::take(&mut self.visited)
+    }
+
+    pub fn exit_frame(&mut self, visited: HashSet<(i32, String)>) {
+        self.visited = visited;
+    }
+
+    // Called before the execution runs a function graph, and paired with
+    // exit_call once it returns
+    pub fn enter_call(&mut self) -> Result<(), ExecutionError> {
+        if self.call_depth >= self.limits.max_call_depth {
+            return Err(ExecutionError::CallDepth {
+                limit: self.limits.max_call_depth,
+            });
+        }
+
+        self.call_depth += 1;
+
+        Ok(())
+    }
+
+    pub fn exit_call(&mut self) {
+        self.call_depth -= 1;
+    }
+
+    // Values are also stored on the IO, which keeps the last one given to it
+    // for inspecting. Reading IO that the execution hasn't given a value, like
+    // the outputs of nodes it didn't run, gets that last value.
+    pub fn input_value(&self, node: &Node, input: &DataInput) -> Arc<Value> {
+        self.inputs
+            .get(&(node.graph_id, node.id, input.name.clone()))
+            .cloned()
+            .unwrap_or_else(|| input.get_value())
+    }
+
+    pub fn set_input_value(&mut self, node: &Node, input: &DataInput, value: Arc<Value>) {
+        input.set_value(value.clone());
+        self.inputs
+            .insert((node.graph_id, node.id, input.name.clone()), value);
+    }
+
+    pub fn output_value(&self, node: &Node, output: &DataOutput) -> Arc<Value> {
+        self.outputs
+            .get(&(node.graph_id, node.id, output.name.clone()))
+            .cloned()
+            .unwrap_or_else(|| output.value.load_full())
+    }
+
+    pub fn set_output_value(&mut self, node: &Node, output: &DataOutput, value: Arc<Value>) {
+        output.value.store(value.clone());
+        self.outputs
+            .insert((node.graph_id, node.id, output.name.clone()), value);
+    }
+
+    // The node's input values, for running it
+    pub fn io_data(&self, node: &Node) -> IOProxy {
+        let inputs = node
+            .inputs
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(|input| match input {
+                Input::Data(input) => Some((
+                    input.name.clone(),
+                    self.input_value(node, input).as_ref().clone(),
+                )),
+                _ => None,
+            })
+            .collect();
+
+        IOProxy {
+            inputs,
+            outputs: HashMap::new(),
+        }
+    }
+
+    // Sets the output values that running the node produced
+    pub fn parse_io_data(&mut self, node: &Node, data: IOProxy) {
+        let outputs: Vec<_> = node
+            .outputs
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(|output| match output {
+                Output::Data(output) => Some(output.clone()),
+                _ => None,
+            })
+            .collect();
+
+        for (name, value) in data.outputs {
+            if let Some(output) = outputs.iter().find(|output| output.name == name) {
+                self.set_output_value(node, output, Arc::new(value));
+            }
+        }
+    }
+
+    // Resolves once the execution is cancelled. Doesn't borrow the execution
+    // so that it can be awaited alongside code that uses the execution.
+    pub fn cancelled(&self) -> impl Future<Output = ()> {
+        let mut receiver = self.cancel_receiver.clone();
+
+        async move {
+            while !*receiver.borrow() {
+                if receiver.changed().await.is_err() {
+                    futures::future::pending::<()>().await;
+                }
+            }
+        }
+    }
+}
+
+impl Drop for Execution {
+    fn drop(&mut self) {
+        self.executions
+            .inner
+            .lock()
+            .unwrap()
+            .running
+            .remove(&self.id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: ExecutionSource = ExecutionSource { graph: 0, node: 0 };
+
+    fn limits(max_steps: usize, detect_cycles: bool) -> ExecutionLimits {
+        ExecutionLimits {
+            max_steps,
+            detect_cycles,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn stops_at_step_limit() {
+        let executions = Executions::default();
+        let mut execution = executions.start(limits(2, true), SOURCE);
+
+        assert_eq!(execution.step(0, ""), Ok(()));
+        assert_eq!(execution.tick(), Ok(()));
+        assert_eq!(
+            execution.step(1, ""),
+            Err(ExecutionError::StepLimit { limit: 2 })
+        );
+    }
+
+    #[test]
+    fn detects_cycles() {
+        let executions = Executions::default();
+        let mut execution = executions.start(limits(10, true), SOURCE);
+
+        assert_eq!(execution.step(0, "A"), Ok(()));
+        assert_eq!(execution.step(0, "B"), Ok(()));
+        assert_eq!(
+            execution.step(0, "A"),
+            Err(ExecutionError::Cycle {
+                node: 0,
+                input: "A".into()
+            })
+        );
+    }
+
+    #[test]
+    fn cycles_can_be_allowed() {
+        let executions = Executions::default();
+        let mut execution = executions.start(limits(10, false), SOURCE);
+
+        assert_eq!(execution.step(0, ""), Ok(()));
+        assert_eq!(execution.step(0, ""), Ok(()));
+    }
+
+    #[test]
+    fn frames_have_their_own_visited_inputs() {
+        let executions = Executions::default();
+        let mut execution = executions.start(limits(10, true), SOURCE);
+
+        execution.step(0, "").unwrap();
+
+        let visited = execution.enter_frame();
+        assert_eq!(execution.step(0, ""), Ok(()));
+        execution.exit_frame(visited);
+
+        assert!(execution.step(0, "").is_err());
+    }
+
+    #[test]
+    fn stops_at_call_depth_limit() {
+        let executions = Executions::default();
+        let mut execution = executions.start(
+            ExecutionLimits {
+                max_call_depth: 2,
+                ..Default::default()
+            },
+            SOURCE,
+        );
+
+        assert_eq!(execution.enter_call(), Ok(()));
+        assert_eq!(execution.enter_call(), Ok(()));
+        assert_eq!(
+            execution.enter_call(),
+            Err(ExecutionError::CallDepth { limit: 2 })
+        );
+
+        execution.exit_call();
+        assert_eq!(execution.enter_call(), Ok(()));
+    }
+
+    #[test]
+    fn cancelling_stops_execution() {
+        let executions = Executions::default();
+        let mut execution = executions.start(limits(10, true), SOURCE);
+        let mut other = executions.start(limits(10, true), SOURCE);
+
+        executions.cancel(Some(execution.id)).unwrap();
+
+        assert_eq!(execution.tick(), Err(ExecutionError::Cancelled));
+        assert_eq!(other.tick(), Ok(()));
+        futures::executor::block_on(execution.cancelled());
+    }
+
+    #[test]
+    fn cancelling_all_executions() {
+        let executions = Executions::default();
+        let mut first = executions.start(limits(10, true), SOURCE);
+        let mut second = executions.start(limits(10, true), ExecutionSource { graph: 0, node: 1 });
+
+        executions.cancel(None).unwrap();
+
+        assert_eq!(first.tick(), Err(ExecutionError::Cancelled));
+        assert_eq!(second.tick(), Err(ExecutionError::Cancelled));
+    }
+
+    #[test]
+    fn cancelling_unknown_execution_fails() {
+        let executions = Executions::default();
+
+        assert_eq!(
+            executions.cancel(Some(0)),
+            Err(CoreError::UnknownExecution { execution: 0 })
+        );
+    }
+
+    #[test]
+    fn finished_executions_stop_running() {
+        let executions = Executions::default();
+        let execution = executions.start(limits(10, true), SOURCE);
+
+        assert!(executions.is_running(SOURCE));
+        drop(execution);
+        assert!(!executions.is_running(SOURCE));
+        assert!(executions.is_empty());
+    }
+}
@@ -0,0 +1,502 @@
+use std::{
+    any::Any,
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    time::Instant,
+};
+
+use arc_swap::ArcSwap;
+use futures::future::{BoxFuture, FutureExt};
+use macrograph_package_api::{
+    schema::{FlowContext, FlowStep, NodeSchemaType},
+    types::FlowFn,
+    EngineRequest, ExecuteContext,
+};
+use tokio::{runtime::Handle, sync::mpsc::UnboundedSender};
+
+use crate::{
+    debugger::{Debugger, PausedExecution, Resume},
+    execution::{Execution, ExecutionError},
+    function::FunctionSchema,
+    io::{ExecOutput, Input, Output},
+    node::Node,
+    notification::{CoreNotification, Notifier},
+    package::{Engine, Package},
+    schema::NodeSchema,
+    trace::{TraceEvent, Tracer},
+    ExecuteFn,
+};
+
+#[derive(Clone)]
+struct PackageHandle {
+    handle: Handle,
+    // None if the package has no engine
+    request_sender: Option<Arc<ArcSwap<UnboundedSender<EngineRequest>>>>,
+    // Set if the package has an engine that hasn't been started yet
+    engine_pending: bool,
+}
+
+// Everything an execution needs from the Core, so that executions can run as
+// their own tasks while the Core keeps processing requests. Nodes are reached
+// through the connections of the node that fired, so the graphs themselves
+// aren't needed. The Core's own schemas hold what they need from it.
+#[derive(Clone)]
+pub(crate) struct Executor {
+    packages: Arc<HashMap<String, PackageHandle>>,
+    tracer: Tracer,
+    debugger: Debugger,
+    notifier: Notifier,
+}
+
+impl Executor {
+    pub fn new(
+        packages: &[Package],
+        tracer: Tracer,
+        debugger: Debugger,
+        notifier: Notifier,
+    ) -> Self {
+        let packages = packages
+            .iter()
+            .map(|package| {
+                let (request_sender, engine_pending) = match &package.engine {
+                    Some(Engine::Running { request_sender, .. }) => {
+                        (Some(request_sender.clone()), false)
+                    }
+                    Some(Engine::Created { .. }) => (None, true),
+                    None => (None, false),
+                };
+
+                (
+                    package.name.clone(),
+                    PackageHandle {
+                        handle: package.runtime.handle().clone(),
+                        request_sender,
+                        engine_pending,
+                    },
+                )
+            })
+            .collect();
+
+        Self {
+            packages: Arc::new(packages),
+            tracer,
+            debugger,
+            notifier,
+        }
+    }
+
+    fn execute_context(&self, package: &str, node: &Node) -> Option<ExecuteContext> {
+        let package = self.packages.get(package)?;
+
+        if package.engine_pending {
+            log::warn!("Attempted to execute node before engine ran");
+            return None;
+        }
+
+        Some(ExecuteContext::new(
+            package
+                .request_sender
+                .as_ref()
+                .map(|sender| sender.load().as_ref().clone()),
+            package.handle.clone(),
+            node.state.clone(),
+        ))
+    }
+
+    // Pure nodes are evaluated lazily when something pulls from their outputs.
+    // `evaluated` is shared for a single pass so that a Pure node feeding
+    // multiple inputs is only run once.
+    fn pull_data_inputs<'a>(
+        &'a self,
+        execution: &'a mut Execution,
+        node: &'a Arc<Node>,
+        evaluated: &'a mut HashSet<i32>,
+    ) -> BoxFuture<'a, ()> {
+        async move {
+            let inputs: Vec<_> = node
+                .inputs
+                .lock()
+                .unwrap()
+                .iter()
+                .filter_map(|input| match input {
+                    Input::Data(input) => Some(input.clone()),
+                    _ => None,
+                })
+                .collect();
+
+            for input in inputs {
+                let output = input.connected_output.lock().unwrap().upgrade();
+
+                // The output's node may have been deleted while executing
+                match output.and_then(|output| Some((output.node.upgrade()?, output))) {
+                    Some((output_node, output)) => {
+                        if let NodeSchemaType::Pure { .. } = ***output_node.schema() {
+                            if evaluated.insert(output_node.id) {
+                                self.evaluate_pure_node(execution, &output_node, evaluated)
+                                    .await;
+                            }
+                        }
+
+                        let value = execution.output_value(&output_node, &output);
+                        execution.set_input_value(node, &input, value);
+                    }
+                    None => {
+                        execution.set_input_value(node, &input, Arc::new(input.unconnected_value()))
+                    }
+                }
+            }
+        }
+        .boxed()
+    }
+
+    async fn evaluate_pure_node(
+        &self,
+        execution: &mut Execution,
+        node: &Arc<Node>,
+        evaluated: &mut HashSet<i32>,
+    ) {
+        self.pull_data_inputs(execution, node, evaluated).await;
+
+        let schema = node.schema();
+
+        if let Some(core) = &schema.core {
+            return core.evaluate(execution, node);
+        }
+
+        if let NodeSchemaType::Pure { execute } = &***schema {
+            self.run_execute_fn(execution, node, None, execute, |_| None)
+                .await;
+        }
+    }
+
+    // Runs a package's execute function on the node's IO, returning None
+    // without running it if the package's engine hasn't started
+    async fn run_execute_fn<T>(
+        &self,
+        execution: &mut Execution,
+        node: &Arc<Node>,
+        input: Option<&str>,
+        execute: &ExecuteFn<T>,
+        output: fn(&T) -> Option<&'static str>,
+    ) -> Option<T> {
+        let context = self.execute_context(&node.schema().package, node)?;
+
+        let mut io_data = execution.io_data(node);
+        let trace = self.tracer.enter_node(execution.id, node, input, &io_data);
+
+        let result = match execute {
+            ExecuteFn::Sync(execute) => execute(&mut io_data, context),
+            ExecuteFn::Async(execute) => execute(&mut io_data, context).await,
+        };
+
+        self.tracer.exit_node(trace, output(&result), &io_data);
+        execution.parse_io_data(node, io_data);
+
+        Some(result)
+    }
+
+    // Waits at the node until the debugger resumes the execution
+    async fn pause(&self, execution: &mut Execution, node: &Arc<Node>) {
+        let outputs = node
+            .outputs
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(|output| match output {
+                Output::Data(output) => Some((
+                    output.name.clone(),
+                    execution.output_value(node, output).as_ref().clone(),
+                )),
+                _ => None,
+            })
+            .collect();
+
+        let paused = PausedExecution {
+            execution: execution.id,
+            graph: node.graph_id,
+            node: node.id,
+            inputs: execution.io_data(node).inputs,
+            outputs,
+        };
+
+        // Registered before anyone is told, so that they can resume it right away
+        let (_guard, resume) = self.debugger.pause(paused.clone());
+
+        self.tracer
+            .emit(|| TraceEvent::ExecutionPaused(paused.clone()));
+        self.notifier
+            .notify(CoreNotification::ExecutionPaused(paused));
+
+        execution.step_over = resume.await == Ok(Resume::StepOver);
+
+        self.tracer.emit(|| TraceEvent::ExecutionResumed {
+            execution: execution.id,
+        });
+        self.notifier.notify(CoreNotification::ExecutionResumed {
+            execution: execution.id,
+        });
+    }
+
+    async fn execute_node(
+        &self,
+        execution: &mut Execution,
+        node: &Arc<Node>,
+        input: &str,
+    ) -> Result<Option<&'static str>, ExecutionError> {
+        self.pull_data_inputs(execution, node, &mut HashSet::new())
+            .await;
+
+        if execution.step_over || node.has_breakpoint() {
+            self.pause(execution, node).await;
+        }
+
+        let schema = node.schema();
+
+        // The Core's own schemas run with what they hold from the Core
+        if let Some(core) = &schema.core {
+            return core.execute(self, execution, node).await;
+        }
+
+        match &***schema {
+            NodeSchemaType::Base { execute } => Ok(self
+                .run_execute_fn(execution, node, Some(input), execute, |output| *output)
+                .await
+                .flatten()),
+            NodeSchemaType::Exec { execute } => Ok(self
+                .run_execute_fn(execution, node, Some(input), execute, |_| Some(""))
+                .await
+                .map(|_| "")),
+            NodeSchemaType::Flow { execute } => {
+                self.run_flow(execution, node, input, *execute).await
+            }
+            NodeSchemaType::Pure { .. } | NodeSchemaType::Event { .. } => Ok(None),
+        }
+    }
+
+    // Calls the Flow node until it stops branching, running each branch to
+    // completion before calling it again
+    fn run_flow<'a>(
+        &'a self,
+        execution: &'a mut Execution,
+        node: &'a Arc<Node>,
+        input: &'a str,
+        execute: FlowFn,
+    ) -> BoxFuture<'a, Result<Option<&'static str>, ExecutionError>> {
+        async move {
+            let mut iteration = 0;
+
+            loop {
+                if iteration > 0 {
+                    execution.tick()?;
+                    // Loops can run for a while without awaiting anything else
+                    tokio::task::yield_now().await;
+                    self.pull_data_inputs(execution, node, &mut HashSet::new())
+                        .await;
+                }
+
+                let mut io_data = execution.io_data(node);
+                let trace = self
+                    .tracer
+                    .enter_node(execution.id, node, Some(input), &io_data);
+
+                let step = {
+                    let mut state = node.state.lock().unwrap();
+                    execute(
+                        &mut io_data,
+                        &mut FlowContext::new(input, iteration, &mut state),
+                    )
+                };
+
+                let output = match step {
+                    FlowStep::Branch(output) | FlowStep::Then(output) => Some(output),
+                    FlowStep::Done => None,
+                };
+
+                self.tracer.exit_node(trace, output, &io_data);
+                execution.parse_io_data(node, io_data);
+
+                match step {
+                    FlowStep::Branch(output) => {
+                        let visited = execution.enter_frame();
+                        let res = self
+                            .run_from(execution, node.find_exec_output(output))
+                            .await;
+                        execution.exit_frame(visited);
+                        res?;
+                    }
+                    FlowStep::Then(output) => return Ok(Some(output)),
+                    FlowStep::Done => return Ok(None),
+                }
+
+                iteration += 1;
+            }
+        }
+        .boxed()
+    }
+
+    // Runs the function graph from its input node with the call's inputs,
+    // setting the call's outputs from the output node that execution ends at
+    pub(crate) fn call_function<'a>(
+        &'a self,
+        execution: &'a mut Execution,
+        node: &'a Arc<Node>,
+        input_schema: &'a Arc<NodeSchema>,
+    ) -> BoxFuture<'a, Result<Option<&'static str>, ExecutionError>> {
+        async move {
+            let entry = {
+                let instances = input_schema.instances.lock().await;
+                instances.iter().min_by_key(|entry| entry.id)
+            };
+
+            let entry = match entry {
+                Some(entry) => entry,
+                None => return Ok(Some("")),
+            };
+
+            for output in entry.outputs.lock().unwrap().iter() {
+                if let (Output::Data(output), Some(input)) =
+                    (output, node.find_data_input(output.get_name()))
+                {
+                    let value = execution.input_value(node, &input);
+                    execution.set_output_value(&entry, output, value);
+                }
+            }
+
+            execution.enter_call()?;
+            let visited = execution.enter_frame();
+            let exit = self.run_from(execution, entry.find_exec_output("")).await;
+            execution.exit_frame(visited);
+            execution.exit_call();
+
+            if let Some(exit) = exit? {
+                if let Some(FunctionSchema::Output { .. }) = exit.schema().function() {
+                    for input in exit.inputs.lock().unwrap().iter() {
+                        if let (Input::Data(input), Some(output)) =
+                            (input, node.find_data_output(input.get_name()))
+                        {
+                            let value = execution.input_value(&exit, input);
+                            execution.set_output_value(node, &output, value);
+                        }
+                    }
+                }
+            }
+
+            Ok(Some(""))
+        }
+        .boxed()
+    }
+
+    // Runs an execution from start to finish, reporting it to the tracer
+    pub async fn run(
+        &self,
+        execution: &mut Execution,
+        node: &Arc<Node>,
+        data: &(dyn Any + Sync),
+    ) -> Result<(), ExecutionError> {
+        let started = Instant::now();
+
+        self.tracer.emit(|| TraceEvent::ExecutionStarted {
+            execution: execution.id,
+            graph: node.graph_id,
+            node: node.id,
+        });
+        self.notifier.notify(CoreNotification::ExecutionStarted {
+            execution: execution.id,
+            graph: node.graph_id,
+            node: node.id,
+        });
+
+        let result = self.fire_node(execution, node, data).await;
+
+        self.tracer.emit(|| TraceEvent::ExecutionFinished {
+            execution: execution.id,
+            graph: node.graph_id,
+            node: node.id,
+            duration_ms: started.elapsed().as_secs_f64() * 1000.0,
+            error: result.clone().err(),
+        });
+        self.notifier.notify(CoreNotification::ExecutionFinished {
+            execution: execution.id,
+            error: result.clone().err(),
+        });
+
+        result
+    }
+
+    async fn fire_node(
+        &self,
+        execution: &mut Execution,
+        node: &Arc<Node>,
+        data: &(dyn Any + Sync),
+    ) -> Result<(), ExecutionError> {
+        let fire = match ***node.schema() {
+            NodeSchemaType::Event { fire } => fire,
+            _ => return Ok(()),
+        };
+
+        let mut io_data = execution.io_data(node);
+        let trace = self.tracer.enter_node(execution.id, node, None, &io_data);
+
+        let output = (fire)(&mut io_data, data);
+
+        self.tracer.exit_node(trace, output, &io_data);
+        execution.parse_io_data(node, io_data);
+
+        self.run_from(execution, output.and_then(|id| node.find_exec_output(id)))
+            .await?;
+
+        Ok(())
+    }
+
+    // Follows exec connections starting at output, returning the last node executed
+    async fn run_from(
+        &self,
+        execution: &mut Execution,
+        output: Option<Arc<ExecOutput>>,
+    ) -> Result<Option<Arc<Node>>, ExecutionError> {
+        let mut target_output_mut = output;
+        let mut last_node = None;
+
+        while let Some(target_output) = target_output_mut.as_ref() {
+            let connected_input = {
+                let o = target_output.clone();
+                let i = &*o.connected_input.lock().unwrap();
+                i.clone()
+            };
+
+            target_output_mut = match connected_input
+                .upgrade()
+                .and_then(|input| Some((input.node.upgrade()?, input)))
+            {
+                Some((node, connected_input)) => {
+                    let output = self
+                        .execute_step(execution, &node, &connected_input.name)
+                        .await?
+                        .and_then(|id| node.find_exec_output(id));
+
+                    last_node = Some(node);
+                    output
+                }
+                None => None,
+            }
+        }
+
+        Ok(last_node)
+    }
+
+    async fn execute_step(
+        &self,
+        execution: &mut Execution,
+        node: &Arc<Node>,
+        input: &str,
+    ) -> Result<Option<&'static str>, ExecutionError> {
+        execution.step(node.id, input)?;
+
+        let cancelled = execution.cancelled();
+
+        tokio::select! {
+            output = self.execute_node(execution, node, input) => output,
+            _ = cancelled => Err(ExecutionError::Cancelled),
+        }
+    }
+}
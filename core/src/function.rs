@@ -0,0 +1,149 @@
+use std::sync::Arc;
+
+use macrograph_package_api::{
+    schema::{ExecuteFn, NodeSchema as NodeSchemaInner},
+    value::types::ValueType,
+    BuildSchema,
+};
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::{
+    execution::{Execution, ExecutionError},
+    executor::Executor,
+    node::Node,
+    schema::{CoreSchema, NodeSchema},
+};
+
+// Function schemas are provided by this package, which is always loaded
+pub const FUNCTION_PACKAGE: &str = "Project";
+
+#[derive(TS, Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[ts(export)]
+pub struct FunctionParameter {
+    pub name: String,
+    pub r#type: ValueType,
+}
+
+#[derive(TS, Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+#[ts(export)]
+pub struct FunctionSignature {
+    pub inputs: Vec<FunctionParameter>,
+    pub outputs: Vec<FunctionParameter>,
+}
+
+// Function schemas are created by the Core rather than a package, so their IO
+// comes from the function's signature instead of a BuildFn
+#[derive(Clone)]
+pub(crate) enum FunctionSchema {
+    // Runs the function graph, starting from an instance of input
+    Call {
+        graph: i32,
+        signature: FunctionSignature,
+        input: Arc<NodeSchema>,
+    },
+    // Where execution enters the function graph, outputting the call's inputs
+    Input {
+        graph: i32,
+        signature: FunctionSignature,
+    },
+    // Where execution leaves the function graph, taking the call's outputs
+    Output {
+        graph: i32,
+        signature: FunctionSignature,
+    },
+}
+
+impl FunctionSchema {
+    pub fn graph(&self) -> i32 {
+        match self {
+            Self::Call { graph, .. } | Self::Input { graph, .. } | Self::Output { graph, .. } => {
+                *graph
+            }
+        }
+    }
+
+    pub fn build(&self, schema: &mut BuildSchema) {
+        match self {
+            Self::Call { signature, .. } => {
+                schema.exec_input("");
+                schema.exec_output("");
+
+                for input in &signature.inputs {
+                    schema.data_input(&input.name, input.r#type);
+                }
+                for output in &signature.outputs {
+                    schema.data_output(&output.name, output.r#type);
+                }
+            }
+            Self::Input { signature, .. } => {
+                schema.exec_output("");
+
+                for input in &signature.inputs {
+                    schema.data_output(&input.name, input.r#type);
+                }
+            }
+            Self::Output { signature, .. } => {
+                schema.exec_input("");
+
+                for output in &signature.outputs {
+                    schema.data_input(&output.name, output.r#type);
+                }
+            }
+        }
+    }
+
+    pub async fn execute(
+        &self,
+        executor: &Executor,
+        execution: &mut Execution,
+        node: &Arc<Node>,
+    ) -> Result<Option<&'static str>, ExecutionError> {
+        match self {
+            Self::Call { input, .. } => executor.call_function(execution, node, input).await,
+            // Output nodes only need their inputs pulled, which the call reads
+            Self::Input { .. } | Self::Output { .. } => Ok(None),
+        }
+    }
+}
+
+// Creates the schemas for a function graph: the node that calls it, followed by
+// its input and output nodes
+pub(crate) fn function_schemas(
+    graph: i32,
+    name: &str,
+    signature: &FunctionSignature,
+) -> [Arc<NodeSchema>; 3] {
+    let input = function_schema(
+        &format!("{} Input", name),
+        FunctionSchema::Input {
+            graph,
+            signature: signature.clone(),
+        },
+    );
+    let output = function_schema(
+        &format!("{} Output", name),
+        FunctionSchema::Output {
+            graph,
+            signature: signature.clone(),
+        },
+    );
+    let call = function_schema(
+        name,
+        FunctionSchema::Call {
+            graph,
+            signature: signature.clone(),
+            input: input.clone(),
+        },
+    );
+
+    [call, input, output]
+}
+
+fn function_schema(name: &str, function: FunctionSchema) -> Arc<NodeSchema> {
+    // Never run, since function schemas are run as a CoreSchema
+    let mut inner = NodeSchemaInner::new_base(name, |_| {}, ExecuteFn::Sync(|_, _| None));
+    inner.package = FUNCTION_PACKAGE.to_string();
+
+    Arc::new(NodeSchema::new_core(inner, CoreSchema::Function(function)))
+}
@@ -1,3 +1,4 @@
+use crate::function::FunctionSignature;
 use crate::node::{Node, Position};
 use crate::schema::NodeSchema;
 use std::{collections::HashMap, sync::Arc};
@@ -6,6 +7,8 @@ pub struct Graph {
     pub id: i32,
     pub name: String,
     pub nodes: HashMap<i32, Arc<Node>>,
+    // Set if the graph can be called from other graphs
+    pub function: Option<FunctionSignature>,
     id_counter: i32,
 }
 
@@ -16,6 +19,7 @@ impl Graph {
             name,
             id_counter: 0,
             nodes: HashMap::new(),
+            function: None,
         }
     }
 
@@ -39,8 +43,13 @@ impl Graph {
         node.clone()
     }
 
-    pub fn delete_node(&mut self, node: i32) {
-        self.nodes.remove(&node);
+    // Adds a node that was previously removed from the graph, keeping its ID
+    pub fn insert_node(&mut self, node: Arc<Node>) {
+        self.nodes.insert(node.id, node);
+    }
+
+    pub fn delete_node(&mut self, node: i32) -> Option<Arc<Node>> {
+        self.nodes.remove(&node)
     }
 
     pub fn node(&self, id: i32) -> Option<&Arc<Node>> {
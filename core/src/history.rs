@@ -0,0 +1,245 @@
+use std::{collections::HashMap, sync::Arc};
+
+use macrograph_package_api::{primitive::Primitive, value::types::ValueType};
+
+use crate::{
+    node::{Node, Position},
+    project::SerializedConnection,
+};
+
+// Older transactions are dropped once a graph's history grows past this
+const MAX_TRANSACTIONS: usize = 100;
+
+// A change to a graph that can be reversed. Removed nodes are kept alive by the
+// history so that undoing their removal restores them with the same ID and state.
+#[derive(Clone)]
+pub(crate) enum Command {
+    AddNode(Arc<Node>),
+    RemoveNode(Arc<Node>),
+    Connect(SerializedConnection),
+    Disconnect(SerializedConnection),
+    MoveNode {
+        node: i32,
+        from: Position,
+        to: Position,
+    },
+    SetDefaultValue {
+        node: i32,
+        input: String,
+        from: Primitive,
+        to: Primitive,
+    },
+    SetProperty {
+        node: i32,
+        property: String,
+        from: Primitive,
+        to: Primitive,
+    },
+    // The types of a node's resolved wildcard groups
+    SetWildcards {
+        node: i32,
+        from: HashMap<u32, ValueType>,
+        to: HashMap<u32, ValueType>,
+    },
+    Rename {
+        from: String,
+        to: String,
+    },
+}
+
+impl Command {
+    pub fn inverse(&self) -> Self {
+        match self.clone() {
+            Self::AddNode(node) => Self::RemoveNode(node),
+            Self::RemoveNode(node) => Self::AddNode(node),
+            Self::Connect(connection) => Self::Disconnect(connection),
+            Self::Disconnect(connection) => Self::Connect(connection),
+            Self::MoveNode { node, from, to } => Self::MoveNode {
+                node,
+                from: to,
+                to: from,
+            },
+            Self::SetDefaultValue {
+                node,
+                input,
+                from,
+                to,
+            } => Self::SetDefaultValue {
+                node,
+                input,
+                from: to,
+                to: from,
+            },
+            Self::SetProperty {
+                node,
+                property,
+                from,
+                to,
+            } => Self::SetProperty {
+                node,
+                property,
+                from: to,
+                to: from,
+            },
+            Self::SetWildcards { node, from, to } => Self::SetWildcards {
+                node,
+                from: to,
+                to: from,
+            },
+            Self::Rename { from, to } => Self::Rename { from: to, to: from },
+        }
+    }
+}
+
+// The commands caused by a single request, which are undone and redone together.
+// Commands are applied in order, so implicit changes like the connections
+// dropped by deleting a node come before the change that caused them.
+pub(crate) type Transaction = Vec<Command>;
+
+fn inverse(transaction: &Transaction) -> Transaction {
+    transaction.iter().rev().map(Command::inverse).collect()
+}
+
+#[derive(Default)]
+pub(crate) struct History {
+    undo: Vec<Transaction>,
+    redo: Vec<Transaction>,
+}
+
+impl History {
+    pub fn record(&mut self, transaction: Transaction) {
+        if transaction.is_empty() {
+            return;
+        }
+
+        self.undo.push(transaction);
+        self.redo.clear();
+
+        if self.undo.len() > MAX_TRANSACTIONS {
+            self.undo.remove(0);
+        }
+    }
+
+    // Returns the commands that undo the most recent transaction, which is
+    // only moved to the redo stack by undone once they were applied
+    pub fn next_undo(&self) -> Option<Transaction> {
+        self.undo.last().map(inverse)
+    }
+
+    pub fn undone(&mut self) {
+        if let Some(transaction) = self.undo.pop() {
+            self.redo.push(transaction);
+        }
+    }
+
+    pub fn next_redo(&self) -> Option<Transaction> {
+        self.redo.last().cloned()
+    }
+
+    pub fn redone(&mut self) {
+        if let Some(transaction) = self.redo.pop() {
+            self.undo.push(transaction);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rename(from: &str, to: &str) -> Command {
+        Command::Rename {
+            from: from.into(),
+            to: to.into(),
+        }
+    }
+
+    fn renames(transaction: Option<Transaction>) -> Vec<(String, String)> {
+        transaction
+            .unwrap_or_default()
+            .into_iter()
+            .map(|command| match command {
+                Command::Rename { from, to } => (from, to),
+                _ => panic!("Expected only renames"),
+            })
+            .collect()
+    }
+
+    fn pairs(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(from, to)| (from.to_string(), to.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn undo_inverts_commands_in_reverse() {
+        let mut history = History::default();
+        history.record(vec![rename("A", "B"), rename("B", "C")]);
+
+        assert_eq!(
+            renames(history.next_undo()),
+            pairs(&[("C", "B"), ("B", "A")])
+        );
+    }
+
+    #[test]
+    fn transactions_only_move_once_applied() {
+        let mut history = History::default();
+        history.record(vec![rename("A", "B")]);
+
+        // Failing to apply the undo leaves the transaction where it was
+        history.next_undo();
+        assert_eq!(renames(history.next_undo()), pairs(&[("B", "A")]));
+        assert!(history.next_redo().is_none());
+
+        history.undone();
+        assert!(history.next_undo().is_none());
+        assert_eq!(renames(history.next_redo()), pairs(&[("A", "B")]));
+
+        history.redone();
+        assert!(history.next_redo().is_none());
+        assert_eq!(renames(history.next_undo()), pairs(&[("B", "A")]));
+    }
+
+    #[test]
+    fn recording_clears_redo() {
+        let mut history = History::default();
+        history.record(vec![rename("A", "B")]);
+        history.undone();
+
+        history.record(vec![rename("A", "C")]);
+
+        assert!(history.next_redo().is_none());
+        assert_eq!(renames(history.next_undo()), pairs(&[("C", "A")]));
+    }
+
+    #[test]
+    fn empty_transactions_are_not_recorded() {
+        let mut history = History::default();
+        history.record(vec![rename("A", "B")]);
+        history.undone();
+
+        history.record(vec![]);
+
+        assert!(history.next_undo().is_none());
+        assert!(history.next_redo().is_some());
+    }
+
+    #[test]
+    fn oldest_transactions_are_dropped() {
+        let mut history = History::default();
+
+        for i in 0..=MAX_TRANSACTIONS {
+            history.record(vec![rename(&i.to_string(), &(i + 1).to_string())]);
+        }
+
+        for _ in 0..MAX_TRANSACTIONS {
+            assert!(history.next_undo().is_some());
+            history.undone();
+        }
+
+        assert!(history.next_undo().is_none());
+        assert_eq!(renames(history.next_redo()), pairs(&[("1", "2")]));
+    }
+}
@@ -1,8 +1,5 @@
-use arc_swap::{access::Access, ArcSwap};
-use macrograph_package_api::{
-    primitive::Primitive,
-    value::types::{PrimitiveType, ValueType},
-};
+use arc_swap::ArcSwap;
+use macrograph_package_api::{primitive::Primitive, value::types::ValueType};
 use weak_table::PtrWeakHashSet;
 
 use crate::{node::Node, value::Value};
@@ -18,10 +15,11 @@ pub struct DataInput {
 }
 
 impl DataInput {
+    #[allow(clippy::new_ret_no_self)]
     pub fn new(name: String, r#type: ValueType, node: &Arc<Node>) -> Input {
         let value = Arc::new(match r#type {
             ValueType::Primitive(primitive) => primitive.into(),
-            ValueType::List(list_type) => Primitive::Bool(false),
+            ValueType::List(_) | ValueType::Wildcard(_) => Primitive::Bool(false),
         });
 
         Input::Data(Arc::new(Self {
@@ -50,13 +48,16 @@ impl DataInput {
         }
     }
 
-    pub fn reset_value(&self) {
+    // The value the input has while it isn't connected
+    pub fn unconnected_value(&self) -> Value {
         match self.r#type {
-            ValueType::Primitive(_) => self
-                .value
-                .swap(Arc::new(self.default_value.load().as_ref().clone().into())),
-            ValueType::List(_) => Arc::new(self.r#type.into()),
-        };
+            ValueType::Primitive(_) => self.default_value.load().as_ref().clone().into(),
+            ValueType::List(_) | ValueType::Wildcard(_) => self.r#type.into(),
+        }
+    }
+
+    pub fn reset_value(&self) {
+        self.value.swap(Arc::new(self.unconnected_value()));
     }
 
     pub fn connect_output(&self, output: &Arc<DataOutput>) {
@@ -88,6 +89,7 @@ pub struct ExecInput {
 }
 
 impl ExecInput {
+    #[allow(clippy::new_ret_no_self)]
     pub fn new(name: String, node: &Arc<Node>) -> Input {
         Input::Exec(Arc::new(Self {
             name,
@@ -133,6 +135,30 @@ impl Input {
             Self::Data(o) => o.disconnect(),
         }
     }
+
+    pub fn node(&self) -> Option<Arc<Node>> {
+        match self {
+            Self::Exec(o) => o.node.upgrade(),
+            Self::Data(o) => o.node.upgrade(),
+        }
+    }
+
+    pub fn connected_output(&self) -> Option<Output> {
+        match self {
+            Self::Exec(o) => o
+                .connected_output
+                .lock()
+                .unwrap()
+                .upgrade()
+                .map(Output::Exec),
+            Self::Data(o) => o
+                .connected_output
+                .lock()
+                .unwrap()
+                .upgrade()
+                .map(Output::Data),
+        }
+    }
 }
 
 pub struct DataOutput {
@@ -144,6 +170,7 @@ pub struct DataOutput {
 }
 
 impl DataOutput {
+    #[allow(clippy::new_ret_no_self)]
     pub fn new(name: String, r#type: ValueType, value: Value, node: &Arc<Node>) -> Output {
         Output::Data(Arc::new(Self {
             name,
@@ -178,6 +205,7 @@ pub struct ExecOutput {
 }
 
 impl ExecOutput {
+    #[allow(clippy::new_ret_no_self)]
     pub fn new(name: String, node: &Arc<Node>) -> Output {
         Output::Exec(Arc::new(Self {
             name,
@@ -223,4 +251,31 @@ impl Output {
             Self::Data(o) => o.disconnect(),
         }
     }
+
+    pub fn node(&self) -> Option<Arc<Node>> {
+        match self {
+            Self::Exec(o) => o.node.upgrade(),
+            Self::Data(o) => o.node.upgrade(),
+        }
+    }
+
+    pub fn connected_inputs(&self) -> Vec<Input> {
+        match self {
+            Self::Exec(o) => o
+                .connected_input
+                .lock()
+                .unwrap()
+                .upgrade()
+                .map(Input::Exec)
+                .into_iter()
+                .collect(),
+            Self::Data(o) => o
+                .connected_inputs
+                .lock()
+                .unwrap()
+                .iter()
+                .map(Input::Data)
+                .collect(),
+        }
+    }
 }
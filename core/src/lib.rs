@@ -1,10 +1,24 @@
 pub mod api;
+pub mod custom_event;
+pub mod debugger;
+pub mod discovery;
+pub mod engine;
+pub mod error;
+pub mod execution;
+pub mod executor;
+pub mod function;
 pub mod graph;
+pub mod history;
 pub mod io;
 pub mod node;
+pub mod notification;
 pub mod package;
+pub mod project;
+pub mod scheduler;
 pub mod schema;
+pub mod trace;
 pub mod value;
+pub mod variable;
 pub mod core;
 
 pub use types::*;
@@ -1,16 +1,23 @@
 use std::{
-    collections::HashMap,
-    sync::{Arc, Mutex},
+    collections::{BTreeMap, HashMap, HashSet},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
 };
 
+use arc_swap::ArcSwap;
 use macrograph_package_api::{
-    value::types::{PrimitiveType, ValueType},
-    BuildSchema, IOProxy, InputSchema, OutputSchema,
+    primitive::Primitive,
+    property::Property,
+    state::SharedNodeState,
+    value::types::{ValueType, WildcardConstraint, WildcardType},
+    BuildSchema, InputSchema, OutputSchema,
 };
 use serde::{Deserialize, Serialize};
 use ts_rs::TS;
 
-use crate::{io::*, schema::NodeSchema, value::Value};
+use crate::{io::*, project::SerializedConnection, schema::NodeSchema};
 
 #[derive(TS, Serialize, Deserialize, Debug, Clone)]
 #[ts(export)]
@@ -19,15 +26,55 @@ pub struct Position {
     y: f64,
 }
 
+impl Position {
+    // The top left corner of the smallest box containing both positions
+    pub fn min(&self, other: &Position) -> Position {
+        Position {
+            x: self.x.min(other.x),
+            y: self.y.min(other.y),
+        }
+    }
+}
+
+impl std::ops::Add for Position {
+    type Output = Position;
+
+    fn add(self, other: Position) -> Position {
+        Position {
+            x: self.x + other.x,
+            y: self.y + other.y,
+        }
+    }
+}
+
+impl std::ops::Sub for Position {
+    type Output = Position;
+
+    fn sub(self, other: Position) -> Position {
+        Position {
+            x: self.x - other.x,
+            y: self.y - other.y,
+        }
+    }
+}
+
 pub struct Node {
     pub id: i32,
     pub graph_id: i32,
     pub position: Mutex<Position>,
-    pub schema: Arc<NodeSchema>,
+    // Swapped out when the schema's package is reloaded
+    pub schema: ArcSwap<NodeSchema>,
+    pub breakpoint: AtomicBool,
     // Structs referencing IO must not hold strong references
     // Dropping the node should also drop the IO, so only use Weaks
     pub inputs: Mutex<Vec<Input>>,
     pub outputs: Mutex<Vec<Output>>,
+    // Declared by the schema when building the node's IO, with the node's values
+    pub properties: Mutex<Vec<Property>>,
+    // The types that connections resolved the node's wildcard groups to
+    pub wildcards: Mutex<HashMap<u32, ValueType>>,
+    // Kept by the node between executions
+    pub state: SharedNodeState,
 }
 
 impl Node {
@@ -38,125 +85,381 @@ impl Node {
             id,
             graph_id,
             position: Mutex::new(position),
-            schema: schema.clone(),
+            schema: ArcSwap::new(schema.clone()),
+            breakpoint: AtomicBool::new(false),
             inputs: Mutex::new(vec![]),
             outputs: Mutex::new(vec![]),
+            properties: Mutex::new(vec![]),
+            wildcards: Mutex::new(HashMap::new()),
+            state: Default::default(),
         });
 
-        let mut ctx = BuildSchema::new();
+        node.reconcile_io(node.build(HashMap::new()));
 
-        schema.build(&mut ctx);
+        node
+    }
 
-        node.reconcile_io(ctx);
+    // Builds the IO the node's schema gives it with the property values
+    pub(crate) fn build(&self, properties: HashMap<String, Primitive>) -> BuildSchema {
+        let mut ctx = BuildSchema::with_properties(properties);
+
+        self.schema().build(&mut ctx);
+
+        let wildcards = self.wildcards.lock().unwrap();
+        let types = ctx
+            .inputs
+            .iter_mut()
+            .filter_map(|input| match input {
+                InputSchema::Data(_, r#type) => Some(r#type),
+                _ => None,
+            })
+            .chain(ctx.outputs.iter_mut().filter_map(|output| match output {
+                OutputSchema::Data(_, r#type) => Some(r#type),
+                _ => None,
+            }));
+
+        for r#type in types {
+            if let ValueType::Wildcard(wildcard) = r#type {
+                if let Some(resolved) = wildcards.get(&wildcard.group) {
+                    *r#type = *resolved;
+                }
+            }
+        }
 
-        node
+        ctx
+    }
+
+    // Rebuilds the node's IO with the properties and resolved wildcards of a
+    // serialized node
+    pub(crate) fn restore(
+        self: &Arc<Self>,
+        properties: BTreeMap<String, Primitive>,
+        wildcards: BTreeMap<u32, ValueType>,
+    ) {
+        if properties.is_empty() && wildcards.is_empty() {
+            return;
+        }
+
+        *self.wildcards.lock().unwrap() = wildcards.into_iter().collect();
+        self.reconcile_io(self.build(properties.into_iter().collect()));
+    }
+
+    // The IO that the schema declares as wildcards, whether or not they're
+    // resolved, as (name, is_input, type)
+    fn declared_wildcards(&self) -> Vec<(String, bool, WildcardType)> {
+        let mut ctx = BuildSchema::with_properties(self.property_values());
+
+        self.schema().build(&mut ctx);
+
+        let inputs = ctx.inputs.into_iter().filter_map(|input| match input {
+            InputSchema::Data(name, ValueType::Wildcard(wildcard)) => Some((name, true, wildcard)),
+            _ => None,
+        });
+        let outputs = ctx.outputs.into_iter().filter_map(|output| match output {
+            OutputSchema::Data(name, ValueType::Wildcard(wildcard)) => {
+                Some((name, false, wildcard))
+            }
+            _ => None,
+        });
+
+        inputs.chain(outputs).collect()
+    }
+
+    fn declared_wildcard(&self, name: &str, is_input: bool) -> Option<WildcardType> {
+        self.declared_wildcards()
+            .into_iter()
+            .find(|(n, i, _)| n == name && *i == is_input)
+            .map(|(_, _, wildcard)| wildcard)
+    }
+
+    // The group along with every group it's connected to through other
+    // nodes' wildcards
+    pub(crate) fn wildcard_set(self: &Arc<Self>, group: u32) -> WildcardSet {
+        let mut set = WildcardSet {
+            members: vec![],
+            constraints: vec![],
+            anchored: false,
+        };
+        let mut pending = vec![(self.clone(), group)];
+
+        while let Some((node, group)) = pending.pop() {
+            let visited = set
+                .members
+                .iter()
+                .any(|(member, g)| Arc::ptr_eq(member, &node) && *g == group);
+
+            if visited {
+                continue;
+            }
+
+            for (name, is_input, wildcard) in node.declared_wildcards() {
+                if wildcard.group != group {
+                    continue;
+                }
+
+                set.constraints.push(wildcard.constraint);
+
+                let connected: Vec<_> = match is_input {
+                    true => node
+                        .find_input(&name)
+                        .and_then(|input| input.connected_output())
+                        .and_then(|output| Some((output.node()?, output.get_name().to_string())))
+                        .into_iter()
+                        .collect(),
+                    false => node
+                        .find_output(&name)
+                        .map(|output| output.connected_inputs())
+                        .unwrap_or_default()
+                        .into_iter()
+                        .filter_map(|input| Some((input.node()?, input.get_name().to_string())))
+                        .collect(),
+                };
+
+                for (other, other_name) in connected {
+                    match other.declared_wildcard(&other_name, !is_input) {
+                        Some(wildcard) => pending.push((other, wildcard.group)),
+                        None => set.anchored = true,
+                    }
+                }
+            }
+
+            set.members.push((node, group));
+        }
+
+        set
+    }
+
+    pub(crate) fn set_wildcards(self: &Arc<Self>, wildcards: HashMap<u32, ValueType>) {
+        *self.wildcards.lock().unwrap() = wildcards;
+        self.reconcile_io(self.build(self.property_values()));
+    }
+
+    // The sets of the node's resolved groups that nothing but other wildcards
+    // is connected to anymore
+    pub(crate) fn released_wildcard_sets(self: &Arc<Self>) -> Vec<WildcardSet> {
+        let groups: Vec<_> = self.wildcards.lock().unwrap().keys().copied().collect();
+
+        groups
+            .into_iter()
+            .map(|group| self.wildcard_set(group))
+            .filter(|set| !set.anchored)
+            .collect()
+    }
+
+    // Builds the node's IO as if the property was set to the value
+    pub(crate) fn build_with_property(&self, name: &str, value: Primitive) -> BuildSchema {
+        let mut properties = self.property_values();
+        properties.insert(name.to_string(), value);
+
+        self.build(properties)
+    }
+
+    pub fn find_property(&self, name: &str) -> Option<Property> {
+        self.properties
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|p| p.name == name)
+            .cloned()
+    }
+
+    pub fn property_values(&self) -> HashMap<String, Primitive> {
+        self.properties
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|p| (p.name.clone(), p.value.clone()))
+            .collect()
+    }
+
+    pub fn schema(&self) -> Arc<NodeSchema> {
+        self.schema.load_full()
     }
 
     pub fn set_position(&self, position: Position) {
         *self.position.lock().unwrap() = position;
     }
 
-    fn reconcile_io(self: &Arc<Self>, ctx: BuildSchema) {
+    pub fn set_breakpoint(&self, enabled: bool) {
+        self.breakpoint.store(enabled, Ordering::Relaxed);
+    }
+
+    pub fn has_breakpoint(&self) -> bool {
+        self.breakpoint.load(Ordering::Relaxed)
+    }
+
+    // Updates the node's IO to match ctx, keeping IO (and its connections) that
+    // still exists with the same kind and type, and replacing IO that doesn't
+    pub(crate) fn reconcile_io(self: &Arc<Self>, ctx: BuildSchema) {
+        *self.properties.lock().unwrap() = ctx.properties;
+
         let mut inputs = self.inputs.lock().unwrap();
         let inputs_count = ctx.inputs.len();
 
         for (index, schema) in ctx.inputs.into_iter().enumerate() {
-            match schema {
-                InputSchema::Exec(name) => match inputs.iter().position(|i| i.get_name() == name) {
-                    Some(input_index) => {
-                        let input = &inputs[input_index];
-
-                        match input {
-                            Input::Exec(_) => {
-                                if input_index != index {
-                                    inputs.swap(input_index, index);
-                                }
+            let name = match &schema {
+                InputSchema::Exec(name) | InputSchema::Data(name, _) => name.clone(),
+            };
+
+            match inputs.iter().position(|i| i.get_name() == name) {
+                Some(input_index) => {
+                    let matches = match (&inputs[input_index], &schema) {
+                        (Input::Exec(_), InputSchema::Exec(_)) => true,
+                        (Input::Data(input), InputSchema::Data(_, r#type)) => {
+                            if let ValueType::Primitive(t) = r#type {
+                                input.set_value(Arc::new((*t).into()));
                             }
-                            _ => {}
+
+                            input.r#type == *r#type
                         }
-                    }
-                    None => inputs.insert(index, ExecInput::new(name, &self)),
-                },
-                InputSchema::Data(name, r#type) => {
-                    match inputs.iter().position(|i| i.get_name() == name) {
-                        Some(input_index) => {
-                            let input = &inputs[input_index];
-
-                            match input {
-                                Input::Data(input) => {
-                                    if input.r#type != r#type {
-                                        input.disconnect();
-                                    }
-
-                                    if let ValueType::Primitive(t) = r#type {
-                                        input.set_value(Arc::new(t.into()));
-                                    }
-
-                                    if input_index != index {
-                                        inputs.swap(input_index, index);
-                                    }
-                                }
-                                _ => {}
+                        _ => false,
+                    };
+
+                    if !matches {
+                        // Resolving or releasing a wildcard keeps its connection,
+                        // since the other side is part of the same wildcard set
+                        let carried = match (&inputs[input_index], &schema) {
+                            (Input::Data(input), InputSchema::Data(_, r#type))
+                                if input.r#type.is_wildcard() || r#type.is_wildcard() =>
+                            {
+                                input.connected_output.lock().unwrap().upgrade()
                             }
+                            _ => None,
+                        };
+
+                        inputs[input_index].disconnect();
+                        inputs[input_index] = match schema {
+                            InputSchema::Exec(name) => ExecInput::new(name, self),
+                            InputSchema::Data(name, r#type) => DataInput::new(name, r#type, self),
+                        };
+
+                        if let (Some(output), Input::Data(input)) = (carried, &inputs[input_index])
+                        {
+                            output.connect_input(input);
+                            input.connect_output(&output);
                         }
-                        None => inputs.insert(index, DataInput::new(name, r#type, &self)),
+                    }
+
+                    if input_index != index {
+                        inputs.swap(input_index, index);
                     }
                 }
+                None => inputs.insert(
+                    index,
+                    match schema {
+                        InputSchema::Exec(name) => ExecInput::new(name, self),
+                        InputSchema::Data(name, r#type) => DataInput::new(name, r#type, self),
+                    },
+                ),
             }
         }
 
-        inputs.drain(inputs_count..);
+        inputs
+            .drain(inputs_count..)
+            .for_each(|input| input.disconnect());
 
         let mut outputs = self.outputs.lock().unwrap();
         let outputs_count = ctx.outputs.len();
 
         for (index, schema) in ctx.outputs.into_iter().enumerate() {
-            match schema {
-                OutputSchema::Exec(name) => {
-                    match outputs.iter().position(|i| i.get_name() == name) {
-                        Some(output_index) => {
-                            let output = &outputs[output_index];
-
-                            match output {
-                                Output::Exec(_) => {
-                                    if output_index != index {
-                                        outputs.swap(output_index, index);
-                                    }
-                                }
-                                _ => {}
-                            }
+            let name = match &schema {
+                OutputSchema::Exec(name) | OutputSchema::Data(name, _) => name.clone(),
+            };
+
+            match outputs.iter().position(|o| o.get_name() == name) {
+                Some(output_index) => {
+                    let matches = match (&outputs[output_index], &schema) {
+                        (Output::Exec(_), OutputSchema::Exec(_)) => true,
+                        (Output::Data(output), OutputSchema::Data(_, r#type)) => {
+                            output.set_value((*r#type).into());
+
+                            output.r#type == *r#type
                         }
-                        None => outputs.insert(index, ExecOutput::new(name, &self)),
-                    }
-                }
-                OutputSchema::Data(name, r#type) => {
-                    match outputs.iter().position(|i| i.get_name() == name) {
-                        Some(output_index) => {
-                            let output = &outputs[output_index];
-
-                            match output {
-                                Output::Data(output) => {
-                                    if output.r#type != r#type {
-                                        output.disconnect();
-                                    }
-
-                                    output.set_value(r#type.into());
-
-                                    if output_index != index {
-                                        outputs.swap(output_index, index);
-                                    }
-                                }
-                                _ => {}
+                        _ => false,
+                    };
+
+                    if !matches {
+                        let carried: Vec<_> = match (&outputs[output_index], &schema) {
+                            (Output::Data(output), OutputSchema::Data(_, r#type))
+                                if output.r#type.is_wildcard() || r#type.is_wildcard() =>
+                            {
+                                output.connected_inputs.lock().unwrap().iter().collect()
+                            }
+                            _ => vec![],
+                        };
+
+                        outputs[output_index].disconnect();
+                        outputs[output_index] = match schema {
+                            OutputSchema::Exec(name) => ExecOutput::new(name, self),
+                            OutputSchema::Data(name, r#type) => {
+                                DataOutput::new(name, r#type, r#type.into(), self)
+                            }
+                        };
+
+                        if let Output::Data(output) = &outputs[output_index] {
+                            for input in carried {
+                                output.connect_input(&input);
+                                input.connect_output(output);
                             }
                         }
-                        None => outputs
-                            .insert(index, DataOutput::new(name, r#type, r#type.into(), &self)),
+                    }
+
+                    if output_index != index {
+                        outputs.swap(output_index, index);
                     }
                 }
+                None => outputs.insert(
+                    index,
+                    match schema {
+                        OutputSchema::Exec(name) => ExecOutput::new(name, self),
+                        OutputSchema::Data(name, r#type) => {
+                            DataOutput::new(name, r#type, r#type.into(), self)
+                        }
+                    },
+                ),
             }
         }
 
-        outputs.drain(outputs_count..);
+        outputs
+            .drain(outputs_count..)
+            .for_each(|output| output.disconnect());
+    }
+
+    // The connections that reconcile_io would drop when given ctx, since the IO
+    // they're connected to would be replaced or removed
+    pub(crate) fn dropped_connections(&self, ctx: &BuildSchema) -> Vec<SerializedConnection> {
+        let inputs = self.inputs.lock().unwrap();
+        let outputs = self.outputs.lock().unwrap();
+
+        let dropped_inputs = inputs.iter().filter(|input| {
+            !ctx.inputs.iter().any(|schema| match (input, schema) {
+                (Input::Exec(input), InputSchema::Exec(name)) => input.name == *name,
+                (Input::Data(input), InputSchema::Data(name, r#type)) => {
+                    input.name == *name && input.r#type == *r#type
+                }
+                _ => false,
+            })
+        });
+        let dropped_outputs = outputs.iter().filter(|output| {
+            !ctx.outputs.iter().any(|schema| match (output, schema) {
+                (Output::Exec(output), OutputSchema::Exec(name)) => output.name == *name,
+                (Output::Data(output), OutputSchema::Data(name, r#type)) => {
+                    output.name == *name && output.r#type == *r#type
+                }
+                _ => false,
+            })
+        });
+
+        dropped_inputs
+            .filter_map(|input| SerializedConnection::new(&input.connected_output()?, input))
+            .chain(dropped_outputs.flat_map(|output| {
+                output
+                    .connected_inputs()
+                    .iter()
+                    .filter_map(|input| SerializedConnection::new(output, input))
+                    .collect::<Vec<_>>()
+            }))
+            .collect()
     }
 
     pub fn find_input(&self, name: &str) -> Option<Input> {
@@ -165,7 +468,7 @@ impl Node {
             .unwrap()
             .iter()
             .find(|i| i.get_name() == name)
-            .map(|i| i.clone())
+            .cloned()
     }
 
     pub fn find_output(&self, name: &str) -> Option<Output> {
@@ -174,7 +477,7 @@ impl Node {
             .unwrap()
             .iter()
             .find(|o| o.get_name() == name)
-            .map(|o| o.clone())
+            .cloned()
     }
 
     pub fn find_data_input(&self, name: &str) -> Option<Arc<DataInput>> {
@@ -217,33 +520,113 @@ impl Node {
         })
     }
 
-    pub fn get_io_data(&self) -> IOProxy {
-        let mut inputs = HashMap::new();
+    pub fn input_connections(&self) -> Vec<SerializedConnection> {
+        self.inputs
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(|input| SerializedConnection::new(&input.connected_output()?, input))
+            .collect()
+    }
 
-        for input in self.inputs.lock().unwrap().iter() {
-            match input {
-                Input::Data(input) => {
-                    inputs.insert(input.name.clone(), (**input.value.load()).clone());
-                }
-                _ => {}
-            };
-        }
+    pub fn output_connections(&self) -> Vec<SerializedConnection> {
+        self.outputs
+            .lock()
+            .unwrap()
+            .iter()
+            .flat_map(|output| {
+                output
+                    .connected_inputs()
+                    .iter()
+                    .filter_map(|input| SerializedConnection::new(output, input))
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
 
-        IOProxy {
-            inputs,
-            outputs: HashMap::new(),
-        }
+    fn upstream_data_nodes(&self) -> Vec<Arc<Node>> {
+        self.inputs
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(|input| match input {
+                Input::Data(input) => input.connected_output.lock().unwrap().upgrade(),
+                _ => None,
+            })
+            .filter_map(|output| output.node.upgrade())
+            .collect()
     }
 
-    pub fn parse_io_data(self: &Arc<Self>, data: IOProxy) {
-        let outputs = self.outputs.lock().unwrap();
+    // Whether the node with `id` is reachable by following data connections upstream
+    pub fn depends_on(&self, id: i32) -> bool {
+        let mut visited = HashSet::new();
+        let mut stack = self.upstream_data_nodes();
 
-        for (name, value) in data.outputs.into_iter() {
-            outputs.iter().find(|o| o.get_name() == name).map(|o| {
-                if let Output::Data(o) = o {
-                    o.set_value(value);
-                }
-            });
+        while let Some(node) = stack.pop() {
+            if node.id == id {
+                return true;
+            }
+
+            if visited.insert(node.id) {
+                stack.extend(node.upstream_data_nodes());
+            }
         }
+
+        false
     }
 }
+
+// A node's wildcard group along with the groups of other nodes' wildcards that
+// it's connected to. Connected wildcards resolve to the same type, so a set is
+// resolved and released as a whole.
+pub(crate) struct WildcardSet {
+    pub members: Vec<(Arc<Node>, u32)>,
+    pub constraints: Vec<WildcardConstraint>,
+    // Whether any of the set's IO is connected to IO that isn't a wildcard
+    pub anchored: bool,
+}
+
+impl WildcardSet {
+    // Whether every wildcard in the set can be resolved to the type
+    pub fn accepts(&self, r#type: &ValueType) -> bool {
+        self.constraints
+            .iter()
+            .all(|constraint| constraint.allows(r#type))
+    }
+
+    // Whether the sets can be joined and still have a type to resolve to
+    pub fn overlaps(&self, other: &WildcardSet) -> bool {
+        self.constraints
+            .iter()
+            .all(|a| other.constraints.iter().all(|b| a.overlaps(b)))
+    }
+}
+
+// Applies update to each member's group of the sets, returning the wildcards
+// of the members that change
+pub(crate) fn update_wildcards(
+    sets: impl IntoIterator<Item = WildcardSet>,
+    update: impl Fn(&mut HashMap<u32, ValueType>, u32),
+) -> Vec<(Arc<Node>, HashMap<u32, ValueType>)> {
+    let mut updated: Vec<(Arc<Node>, HashMap<u32, ValueType>)> = vec![];
+
+    for (member, group) in sets.into_iter().flat_map(|set| set.members) {
+        let position = updated
+            .iter()
+            .position(|(node, _)| Arc::ptr_eq(node, &member));
+
+        let index = match position {
+            Some(index) => index,
+            None => {
+                let wildcards = member.wildcards.lock().unwrap().clone();
+                updated.push((member, wildcards));
+                updated.len() - 1
+            }
+        };
+
+        update(&mut updated[index].1, group);
+    }
+
+    updated.retain(|(node, wildcards)| *node.wildcards.lock().unwrap() != *wildcards);
+    updated
+}
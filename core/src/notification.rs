@@ -0,0 +1,147 @@
+use macrograph_package_api::primitive::Primitive;
+use serde::Serialize;
+use tokio::sync::broadcast;
+use ts_rs::TS;
+
+use crate::{
+    api::{graph::RawGraph, node::RawNode},
+    debugger::PausedExecution,
+    engine::EngineStatus,
+    execution::{ExecutionError, ExecutionId},
+    node::Position,
+    variable::{Variable, VariableId},
+};
+
+// Sent to every client whenever the Core's state changes, regardless of
+// which client caused the change
+#[derive(TS, Serialize, Debug, Clone)]
+#[ts(export)]
+#[serde(tag = "type", content = "data")]
+pub enum CoreNotification {
+    GraphCreated {
+        id: i32,
+        name: String,
+    },
+    GraphRenamed {
+        id: i32,
+        name: String,
+    },
+    GraphReset {
+        graph: i32,
+    },
+    NodeCreated {
+        graph: i32,
+        node: RawNode,
+    },
+    NodeMoved {
+        graph: i32,
+        node: i32,
+        position: Position,
+    },
+    NodeDeleted {
+        graph: i32,
+        node: i32,
+    },
+    DefaultValueSet {
+        graph: i32,
+        node: i32,
+        input: String,
+        value: Primitive,
+    },
+    // Sent with the node's rebuilt IO
+    NodePropertySet {
+        graph: i32,
+        node: RawNode,
+    },
+    // Sent with the node's IO when connections resolve its wildcards, or when
+    // nothing is connected to them anymore
+    WildcardsChanged {
+        graph: i32,
+        node: RawNode,
+    },
+    IOConnected {
+        graph: i32,
+        output_node: i32,
+        output: String,
+        input_node: i32,
+        input: String,
+    },
+    IODisconnected {
+        graph: i32,
+        node: i32,
+        io: String,
+        is_input: bool,
+    },
+    VariableCreated {
+        variable: Variable,
+    },
+    VariableDeleted {
+        variable: VariableId,
+    },
+    // Sent whenever a variable is set, either by a request or a Set node
+    VariableChanged {
+        variable: VariableId,
+        value: Primitive,
+    },
+    BreakpointSet {
+        graph: i32,
+        node: i32,
+        enabled: bool,
+    },
+    ProjectLoaded {
+        graphs: Vec<RawGraph>,
+    },
+    PackageReloaded {
+        package: String,
+        graphs: Vec<RawGraph>,
+    },
+    EngineStatusChanged {
+        package: String,
+        status: EngineStatus,
+    },
+    EventFired {
+        package: String,
+        event: String,
+    },
+    ExecutionStarted {
+        execution: ExecutionId,
+        graph: i32,
+        node: i32,
+    },
+    ExecutionPaused(PausedExecution),
+    ExecutionResumed {
+        execution: ExecutionId,
+    },
+    ExecutionFinished {
+        execution: ExecutionId,
+        error: Option<ExecutionError>,
+    },
+}
+
+#[derive(Clone)]
+pub struct Notifier {
+    sender: broadcast::Sender<CoreNotification>,
+}
+
+impl Notifier {
+    pub fn new() -> Self {
+        Self {
+            sender: broadcast::channel(1024).0,
+        }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<CoreNotification> {
+        self.sender.subscribe()
+    }
+
+    // Notifications are dropped if nobody is subscribed
+    pub fn notify(&self, notification: CoreNotification) {
+        self.sender.send(notification).ok();
+    }
+}
+
+impl Default for Notifier {
+    fn default() -> Self {
+        Self::new()
+    }
+}
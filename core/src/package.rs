@@ -1,26 +1,44 @@
-use std::sync::Arc;
+use std::{
+    path::PathBuf,
+    sync::{mpsc::Sender, Arc, Mutex},
+    thread::JoinHandle,
+    time::{Duration, Instant},
+};
 
+use arc_swap::ArcSwap;
+use libloading::Library;
 use macrograph_package_api::{
-    engine::{InitialEngineState, RunFn},
+    engine::{Event, RunFn, StateFn},
     package::Package as ApiPackage,
+    schema::NodeSchemaType,
     EngineRequest,
 };
-use tokio::sync::mpsc::UnboundedSender;
+use tokio::sync::{
+    mpsc::{unbounded_channel, UnboundedSender},
+    watch,
+};
 
-use crate::schema::NodeSchema;
+use crate::{
+    discovery::PackageManifest,
+    engine::{EngineStatus, EngineSupervisor, RestartPolicy},
+    notification::Notifier,
+    schema::NodeSchema,
+};
 
 pub enum Engine {
     Created {
         run: RunFn,
-        state: InitialEngineState,
+        state: Option<StateFn>,
     },
     Running {
-        request_sender: UnboundedSender<EngineRequest>,
+        request_sender: Arc<ArcSwap<UnboundedSender<EngineRequest>>>,
+        stop_sender: Arc<ArcSwap<watch::Sender<bool>>>,
+        restart_sender: Sender<()>,
     },
 }
 
 impl Engine {
-    pub fn new(run: RunFn, state: InitialEngineState) -> Self {
+    pub fn new(run: RunFn, state: Option<StateFn>) -> Self {
         Self::Created { run, state }
     }
 }
@@ -29,7 +47,15 @@ pub struct Package {
     pub name: String,
     pub schemas: Vec<Arc<NodeSchema>>,
     pub engine: Option<Engine>,
+    pub engine_status: Arc<Mutex<EngineStatus>>,
+    // The supervisor's thread, joined before the library is unloaded
+    pub engine_thread: Option<JoinHandle<()>>,
+    pub manifest: Option<PackageManifest>,
+    // Where the package's library was loaded from, used when reloading
+    pub path: Option<PathBuf>,
     pub runtime: tokio::runtime::Runtime,
+    // Must be dropped last, since schemas and the engine point into it
+    pub library: Option<Arc<Library>>,
 }
 
 impl Package {
@@ -40,21 +66,204 @@ impl Package {
     pub fn schema(&self, name: &str) -> Option<&Arc<NodeSchema>> {
         self.schemas.iter().find(|s| s.name == name)
     }
-}
 
-impl From<ApiPackage> for Package {
-    fn from(api_package: ApiPackage) -> Self {
+    // Custom events can share a name with other schemas in the project's package
+    pub fn event_schema(&self, name: &str) -> Option<&Arc<NodeSchema>> {
+        self.schemas
+            .iter()
+            .find(|s| s.name == name && matches!(*****s, NodeSchemaType::Event { .. }))
+    }
+
+    pub fn engine_status(&self) -> EngineStatus {
+        self.engine_status.lock().unwrap().clone()
+    }
+
+    pub fn start_engine(
+        &mut self,
+        event_sender: UnboundedSender<Event>,
+        notifier: Notifier,
+        policy: RestartPolicy,
+    ) {
+        self.engine = match self.engine.take() {
+            Some(Engine::Created { run, state }) => {
+                // Replaced with live senders each time the engine starts
+                let (request_sender, _) = unbounded_channel();
+                let request_sender = Arc::new(ArcSwap::from_pointee(request_sender));
+                let (stop_sender, _) = watch::channel(false);
+                let stop_sender = Arc::new(ArcSwap::from_pointee(stop_sender));
+                let (restart_sender, restart_receiver) = std::sync::mpsc::channel();
+
+                let thread = EngineSupervisor {
+                    run,
+                    state,
+                    package: self.name.clone(),
+                    handle: self.runtime.handle().clone(),
+                    event_sender,
+                    request_sender: request_sender.clone(),
+                    stop_sender: stop_sender.clone(),
+                    status: self.engine_status.clone(),
+                    notifier,
+                    restart_receiver,
+                    policy,
+                    _library: self.library.clone(),
+                }
+                .spawn();
+                self.engine_thread = Some(thread);
+
+                Some(Engine::Running {
+                    request_sender,
+                    stop_sender,
+                    restart_sender,
+                })
+            }
+            Some(engine) => {
+                log::warn!("Attempted to setup an engine that has already been created");
+                Some(engine)
+            }
+            None => None,
+        }
+    }
+
+    // Asks the engine to stop. Engines only stop if they were started with
+    // run_fn! or check EngineContext::stop_requested themselves.
+    pub fn stop_engine(&mut self) {
+        if let Some(Engine::Running { stop_sender, .. }) = self.engine.take() {
+            stop_sender.load().send(true).ok();
+        }
+    }
+
+    // Stops the engine and shuts down the runtime before dropping the library,
+    // since both run code from it. If either doesn't finish within timeout the
+    // library is leaked rather than unloaded from under them.
+    pub async fn unload(mut self, timeout: Duration) {
+        self.stop_engine();
+
+        let engine_thread = self.engine_thread.take();
+        let runtime = self.runtime;
+
+        // Shutting down a runtime with a timeout panics inside of an async context
+        let stopped = tokio::task::spawn_blocking(move || {
+            let deadline = Instant::now() + timeout;
+
+            if let Some(thread) = engine_thread {
+                while !thread.is_finished() {
+                    if Instant::now() >= deadline {
+                        runtime.shutdown_background();
+                        return false;
+                    }
+
+                    std::thread::sleep(Duration::from_millis(10));
+                }
+            }
+
+            // shutdown_timeout doesn't say whether the runtime's threads finished,
+            // so using up the whole timeout counts as them still running
+            let started = Instant::now();
+            let remaining = deadline.saturating_duration_since(started);
+            runtime.shutdown_timeout(remaining);
+            started.elapsed() < remaining
+        })
+        .await
+        .unwrap_or(false);
+
+        if !stopped {
+            log::warn!(
+                "Package {} did not stop in time, its library will stay loaded",
+                self.name
+            );
+            std::mem::forget(self.library);
+        }
+    }
+
+    pub fn new(api_package: ApiPackage, library: Option<Arc<Library>>) -> Self {
         Self {
             name: api_package.name,
             schemas: api_package
                 .schemas
                 .into_iter()
-                .map(|s| Arc::new(s.into()))
+                .map(|s| Arc::new(NodeSchema::new(s, library.clone())))
                 .collect(),
             engine: api_package
                 .engine_config
                 .map(|config| Engine::new(config.run, config.state)),
+            engine_status: Arc::new(Mutex::new(EngineStatus::Stopped)),
+            engine_thread: None,
+            manifest: None,
+            path: None,
             runtime: api_package.runtime,
+            library,
+        }
+    }
+}
+
+impl From<ApiPackage> for Package {
+    fn from(api_package: ApiPackage) -> Self {
+        Self::new(api_package, None)
+    }
+}
+
+#[derive(Debug)]
+pub enum LoadPackageError {
+    Open(libloading::Error),
+    MissingDescriptor,
+    MissingEntryPoint,
+    ApiVersionMismatch { expected: u32, found: u32 },
+    RustcVersionMismatch { expected: String, found: String },
+    AlreadyLoaded(String),
+    Io(std::io::Error),
+    Manifest(String),
+    MissingLibrary,
+    InvalidVersion(String),
+    IncompatibleCore { required: String, core: String },
+    MissingDependencies(Vec<String>),
+    NameMismatch { manifest: String, package: String },
+    NameChanged { expected: String, found: String },
+}
+
+impl std::fmt::Display for LoadPackageError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Open(e) => write!(f, "Failed to open library: {}", e),
+            Self::MissingDescriptor => write!(
+                f,
+                "Library does not export MACROGRAPH_PACKAGE_API_VERSION, was it built with package_descriptor!()?"
+            ),
+            Self::MissingEntryPoint => write!(f, "Library does not export create_package"),
+            Self::ApiVersionMismatch { expected, found } => write!(
+                f,
+                "Package was built for package API version {}, but the core requires version {}",
+                found, expected
+            ),
+            Self::RustcVersionMismatch { expected, found } => write!(
+                f,
+                "Package was built with '{}', but the core was built with '{}'",
+                found, expected
+            ),
+            Self::AlreadyLoaded(name) => write!(f, "Package '{}' is already loaded", name),
+            Self::Io(e) => write!(f, "{}", e),
+            Self::Manifest(e) => write!(f, "Invalid package manifest: {}", e),
+            Self::MissingLibrary => write!(f, "No library found next to package manifest"),
+            Self::InvalidVersion(version) => write!(f, "Invalid version '{}'", version),
+            Self::IncompatibleCore { required, core } => write!(
+                f,
+                "Package requires core version {} or newer, but the core is version {}",
+                required, core
+            ),
+            Self::MissingDependencies(dependencies) => write!(
+                f,
+                "Package depends on packages that could not be loaded: {}",
+                dependencies.join(", ")
+            ),
+            Self::NameMismatch { manifest, package } => write!(
+                f,
+                "Manifest is for package '{}', but the library provides '{}'",
+                manifest, package
+            ),
+            Self::NameChanged { expected, found } => write!(
+                f,
+                "Reloaded library provides package '{}' instead of '{}'",
+                found, expected
+            ),
         }
     }
 }
@@ -0,0 +1,188 @@
+use std::collections::BTreeMap;
+
+use macrograph_package_api::{primitive::Primitive, value::types::ValueType};
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::{
+    custom_event::CustomEvent,
+    function::FunctionSignature,
+    graph::Graph,
+    io::{Input, Output},
+    node::{Node, Position},
+    variable::Variable,
+};
+
+// Bump whenever the layout of SerializedProject changes in a way older
+// versions of the core can't read
+pub const PROJECT_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SerializedProject {
+    pub version: u32,
+    pub graphs: Vec<SerializedGraph>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub variables: Vec<Variable>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub custom_events: Vec<CustomEvent>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SerializedGraph {
+    pub id: i32,
+    pub name: String,
+    pub nodes: Vec<SerializedNode>,
+    pub connections: Vec<SerializedConnection>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub function: Option<FunctionSignature>,
+}
+
+#[derive(TS, Serialize, Deserialize, Debug, Clone)]
+#[ts(export)]
+pub struct SerializedNode {
+    pub id: i32,
+    pub package: String,
+    pub schema: String,
+    pub position: Position,
+    pub default_values: BTreeMap<String, Primitive>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub properties: BTreeMap<String, Primitive>,
+    // The types that the node's wildcard groups were resolved to
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub wildcards: BTreeMap<u32, ValueType>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub breakpoint: bool,
+    // The node's saved state as JSON
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state: Option<String>,
+}
+
+#[derive(TS, Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[ts(export)]
+pub struct SerializedConnection {
+    pub output_node: i32,
+    pub output: String,
+    pub input_node: i32,
+    pub input: String,
+}
+
+impl SerializedConnection {
+    pub fn new(output: &Output, input: &Input) -> Option<Self> {
+        Some(Self {
+            output_node: output.node()?.id,
+            output: output.get_name().to_string(),
+            input_node: input.node()?.id,
+            input: input.get_name().to_string(),
+        })
+    }
+}
+
+// A selection of nodes copied from a graph. Positions are relative to the
+// selection's top left corner, and only connections between the copied nodes
+// are kept.
+#[derive(TS, Serialize, Deserialize, Debug, Clone)]
+#[ts(export)]
+pub struct SerializedFragment {
+    pub nodes: Vec<SerializedNode>,
+    pub connections: Vec<SerializedConnection>,
+}
+
+#[derive(TS, Serialize, Debug)]
+#[ts(export)]
+pub struct MissingNode {
+    pub graph: i32,
+    pub node: i32,
+    pub package: String,
+    pub schema: String,
+}
+
+#[derive(TS, Serialize, Debug, Default)]
+#[ts(export)]
+pub struct LoadProjectReport {
+    pub missing_nodes: Vec<MissingNode>,
+    pub failed_connections: Vec<SerializedConnection>,
+}
+
+#[derive(Debug)]
+pub enum ProjectError {
+    Io(std::io::Error),
+    Json(serde_json::Error),
+    UnsupportedVersion(u32),
+}
+
+impl std::fmt::Display for ProjectError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "{}", e),
+            Self::Json(e) => write!(f, "{}", e),
+            Self::UnsupportedVersion(v) => write!(
+                f,
+                "Project version {} is newer than supported version {}",
+                v, PROJECT_VERSION
+            ),
+        }
+    }
+}
+
+impl From<std::io::Error> for ProjectError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for ProjectError {
+    fn from(e: serde_json::Error) -> Self {
+        Self::Json(e)
+    }
+}
+
+impl From<&Node> for SerializedNode {
+    fn from(node: &Node) -> Self {
+        let mut default_values = BTreeMap::new();
+
+        for input in node.inputs.lock().unwrap().iter() {
+            if let Input::Data(input) = input {
+                if let ValueType::Primitive(_) = input.r#type {
+                    default_values.insert(
+                        input.name.clone(),
+                        input.default_value.load().as_ref().clone(),
+                    );
+                }
+            }
+        }
+
+        let schema = node.schema();
+
+        Self {
+            id: node.id,
+            package: schema.package.clone(),
+            schema: schema.name.clone(),
+            position: node.position.lock().unwrap().clone(),
+            default_values,
+            properties: node.property_values().into_iter().collect(),
+            wildcards: node.wildcards.lock().unwrap().clone().into_iter().collect(),
+            breakpoint: node.has_breakpoint(),
+            state: node.state.lock().unwrap().save(),
+        }
+    }
+}
+
+impl From<&Graph> for SerializedGraph {
+    fn from(graph: &Graph) -> Self {
+        let mut nodes: Vec<_> = graph.nodes.values().collect();
+        nodes.sort_by_key(|n| n.id);
+
+        let connections = nodes
+            .iter()
+            .flat_map(|node| node.input_connections())
+            .collect();
+
+        Self {
+            id: graph.id,
+            name: graph.name.clone(),
+            nodes: nodes.iter().map(|n| n.as_ref().into()).collect(),
+            connections,
+            function: graph.function.clone(),
+        }
+    }
+}
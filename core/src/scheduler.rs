@@ -0,0 +1,346 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, Weak},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use macrograph_package_api::{
+    schema::{ExecuteFn, NodeSchema as NodeSchemaInner},
+    BuildSchema,
+};
+use tokio::{
+    sync::{
+        mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender},
+        watch,
+    },
+    task::JoinHandle,
+};
+
+use crate::{
+    execution::Execution,
+    node::Node,
+    schema::{CoreSchema, NodeSchema},
+};
+
+// Time schemas are provided by this package, which is always loaded
+pub const TIME_PACKAGE: &str = "Time";
+
+// Intervals are clamped to this so that a timer can't flood the Core with events
+const MIN_INTERVAL: Duration = Duration::from_millis(10);
+// How often At Time nodes check the clock
+const CLOCK_PERIOD: Duration = Duration::from_secs(1);
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum Timer {
+    // Waits before continuing the execution
+    Delay,
+    // Continues once no other execution has entered it for the wait
+    Debounce,
+    // Continues at most once per wait, stopping executions in between
+    Throttle,
+    // Fires every N milliseconds
+    Interval,
+    // Fires once a day at the given UTC time
+    AtTime,
+}
+
+// The schema of a Time package node, which waits on the scheduler
+#[derive(Clone)]
+pub(crate) struct TimerSchema {
+    pub timer: Timer,
+    scheduler: Scheduler,
+}
+
+impl TimerSchema {
+    pub fn build(&self, schema: &mut BuildSchema) {
+        match self.timer {
+            Timer::Delay => {
+                schema.exec_input("");
+                schema.exec_output("");
+                schema.int_input("Duration (ms)");
+            }
+            Timer::Debounce | Timer::Throttle => {
+                schema.exec_input("");
+                schema.exec_output("");
+                schema.int_input("Wait (ms)");
+            }
+            Timer::Interval => {
+                schema.exec_output("");
+                schema.int_input("N");
+            }
+            Timer::AtTime => {
+                schema.exec_output("");
+                schema.string_input("Time");
+            }
+        }
+    }
+
+    // Delay, Debounce and Throttle nodes only continue the execution once the
+    // scheduler says so. Waiting suspends the execution's task, so other
+    // executions and requests are unaffected.
+    pub async fn execute(&self, execution: &Execution, node: &Node) -> Option<&'static str> {
+        let duration = |input: &str| {
+            let ms = node
+                .find_data_input(input)
+                .and_then(|input| execution.input_value(node, &input).as_int())
+                .unwrap_or(0);
+
+            Duration::from_millis(ms.max(0) as u64)
+        };
+
+        let proceed = match self.timer {
+            Timer::Delay => self.scheduler.sleep(node, duration("Duration (ms)")).await,
+            Timer::Debounce => self.scheduler.debounce(node, duration("Wait (ms)")).await,
+            Timer::Throttle => self.scheduler.throttle(node, duration("Wait (ms)")),
+            // Events are fired by the scheduler rather than executed
+            Timer::Interval | Timer::AtTime => false,
+        };
+
+        proceed.then_some("")
+    }
+}
+
+pub(crate) fn time_schemas(scheduler: &Scheduler) -> Vec<Arc<NodeSchema>> {
+    let flow = |name: &str, timer| {
+        // Never run, since the TimerSchema waits on the scheduler itself
+        let inner = NodeSchemaInner::new_base(name, |_| {}, ExecuteFn::Sync(|_, _| None));
+        (inner, timer)
+    };
+    let event = |name: &str, timer| {
+        (
+            NodeSchemaInner::new_event(name, |_| {}, |_, _| Some("")),
+            timer,
+        )
+    };
+
+    [
+        flow("Delay", Timer::Delay),
+        flow("Debounce", Timer::Debounce),
+        flow("Throttle", Timer::Throttle),
+        event("Every N ms", Timer::Interval),
+        event("At Time", Timer::AtTime),
+    ]
+    .into_iter()
+    .map(|(mut inner, timer)| {
+        inner.package = TIME_PACKAGE.to_string();
+
+        let timer = TimerSchema {
+            timer,
+            scheduler: scheduler.clone(),
+        };
+
+        Arc::new(NodeSchema::new_core(inner, CoreSchema::Timer(timer)))
+    })
+    .collect()
+}
+
+struct NodeState {
+    // Dropped when the node is removed, ending any waits on it
+    removed: watch::Sender<()>,
+    debounce: u64,
+    throttled_until: Option<Instant>,
+    timer: Option<JoinHandle<()>>,
+}
+
+impl Default for NodeState {
+    fn default() -> Self {
+        Self {
+            removed: watch::channel(()).0,
+            debounce: 0,
+            throttled_until: None,
+            timer: None,
+        }
+    }
+}
+
+// Keeps track of everything waiting on time for each node, so that it can all
+// be stopped when the node is removed. Shared with executors so that waiting
+// only suspends the execution, not the Core.
+#[derive(Clone)]
+pub(crate) struct Scheduler {
+    nodes: Arc<Mutex<HashMap<(i32, i32), NodeState>>>,
+    // Receives timer nodes whenever they should fire
+    fire: UnboundedSender<Arc<Node>>,
+}
+
+impl Scheduler {
+    pub fn new() -> (Self, UnboundedReceiver<Arc<Node>>) {
+        let (fire, receiver) = unbounded_channel();
+
+        (
+            Self {
+                nodes: Default::default(),
+                fire,
+            },
+            receiver,
+        )
+    }
+
+    // Returns None if the node was removed, since executions can outlive the
+    // nodes they're running and mustn't bring back their state
+    fn with_state<T>(&self, node: &Node, f: impl FnOnce(&mut NodeState) -> T) -> Option<T> {
+        let mut nodes = self.nodes.lock().unwrap();
+        nodes.get_mut(&(node.graph_id, node.id)).map(f)
+    }
+
+    // Returns false if the node was removed before the duration passed
+    pub async fn sleep(&self, node: &Node, duration: Duration) -> bool {
+        let mut removed = match self.with_state(node, |state| state.removed.subscribe()) {
+            Some(removed) => removed,
+            None => return false,
+        };
+
+        tokio::select! {
+            _ = tokio::time::sleep(duration) => true,
+            _ = removed.changed() => false,
+        }
+    }
+
+    // Returns whether nothing else entered the node while waiting
+    pub async fn debounce(&self, node: &Node, wait: Duration) -> bool {
+        let entered = self.with_state(node, |state| {
+            state.debounce += 1;
+            state.debounce
+        });
+
+        entered.is_some()
+            && self.sleep(node, wait).await
+            && self.with_state(node, |state| state.debounce) == entered
+    }
+
+    // Returns whether the node's last wait is over, starting a new one if so
+    pub fn throttle(&self, node: &Node, wait: Duration) -> bool {
+        self.with_state(node, |state| {
+            let now = Instant::now();
+
+            if state.throttled_until.is_some_and(|until| now < until) {
+                return false;
+            }
+
+            state.throttled_until = Some(now + wait);
+            true
+        })
+        .unwrap_or(false)
+    }
+
+    // Keeps track of a node added to a graph until remove_node, starting to
+    // fire it if it's an Every N ms or At Time node. Their inputs are read
+    // from the node's default values each time.
+    pub fn add_node(&self, node: &Arc<Node>) {
+        let (weak, fire) = (Arc::downgrade(node), self.fire.clone());
+
+        let timer = match node.schema().timer() {
+            Some(Timer::Interval) => Some(tokio::spawn(run_interval(weak, fire))),
+            Some(Timer::AtTime) => Some(tokio::spawn(run_at_time(weak, fire))),
+            _ => None,
+        };
+
+        let state = NodeState {
+            timer,
+            ..Default::default()
+        };
+
+        let old = self
+            .nodes
+            .lock()
+            .unwrap()
+            .insert((node.graph_id, node.id), state);
+
+        if let Some(timer) = old.and_then(|state| state.timer) {
+            timer.abort();
+        }
+    }
+
+    pub fn remove_node(&self, graph: i32, node: i32) {
+        let state = self.nodes.lock().unwrap().remove(&(graph, node));
+
+        if let Some(timer) = state.and_then(|state| state.timer) {
+            timer.abort();
+        }
+    }
+
+    pub fn clear(&self) {
+        for (_, state) in self.nodes.lock().unwrap().drain() {
+            if let Some(timer) = state.timer {
+                timer.abort();
+            }
+        }
+    }
+}
+
+async fn run_interval(node: Weak<Node>, fire: UnboundedSender<Arc<Node>>) {
+    loop {
+        let interval = match node.upgrade() {
+            Some(node) => node
+                .find_data_input("N")
+                .and_then(|input| input.default_value.load().as_int())
+                .unwrap_or(0),
+            None => return,
+        };
+
+        tokio::time::sleep(Duration::from_millis(interval.max(0) as u64).max(MIN_INTERVAL)).await;
+
+        let sent = node.upgrade().map(|node| fire.send(node).is_ok());
+
+        if sent != Some(true) {
+            return;
+        }
+    }
+}
+
+async fn run_at_time(node: Weak<Node>, fire: UnboundedSender<Arc<Node>>) {
+    let mut last = seconds_of_day();
+
+    loop {
+        tokio::time::sleep(CLOCK_PERIOD).await;
+
+        let node = match node.upgrade() {
+            Some(node) => node,
+            None => return,
+        };
+
+        let now = seconds_of_day();
+        let time = node
+            .find_data_input("Time")
+            .and_then(|input| input.default_value.load().as_string())
+            .and_then(|time| parse_time(&time));
+
+        // Checks whether the time was passed since the last check, which may
+        // have been before midnight
+        let passed = time.is_some_and(|time| match last <= now {
+            true => last < time && time <= now,
+            false => last < time || time <= now,
+        });
+
+        last = now;
+
+        if passed && fire.send(node).is_err() {
+            return;
+        }
+    }
+}
+
+fn seconds_of_day() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|time| time.as_secs() % SECONDS_PER_DAY)
+        .unwrap_or(0)
+}
+
+// Parses HH:MM or HH:MM:SS into seconds since midnight
+fn parse_time(time: &str) -> Option<u64> {
+    let parts = time
+        .split(':')
+        .map(|part| part.trim().parse().ok())
+        .collect::<Option<Vec<u64>>>()?;
+
+    let (hours, minutes, seconds) = match parts[..] {
+        [hours, minutes] => (hours, minutes, 0),
+        [hours, minutes, seconds] => (hours, minutes, seconds),
+        _ => return None,
+    };
+
+    (hours < 24 && minutes < 60 && seconds < 60).then(|| hours * 3600 + minutes * 60 + seconds)
+}
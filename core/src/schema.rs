@@ -1,14 +1,76 @@
+use libloading::Library;
 use macrograph_package_api::schema::NodeSchema as NodeSchemaInner;
 use macrograph_package_api::BuildSchema;
-use std::{ops::Deref, sync::Weak};
+use std::{
+    ops::Deref,
+    sync::{Arc, Weak},
+};
 use tokio::sync::Mutex;
 use weak_table::PtrWeakHashSet;
 
-use crate::node::Node;
+use crate::{
+    custom_event::CustomEventSchema,
+    execution::{Execution, ExecutionError},
+    executor::Executor,
+    function::FunctionSchema,
+    node::Node,
+    scheduler::{Timer, TimerSchema},
+    variable::{VariableAccess, VariableId, VariableSchema},
+};
+
+// Schemas that the Core creates rather than a package. They hold whatever they
+// need from the Core to run their nodes, so that the executor runs them all
+// the same way.
+#[derive(Clone)]
+pub(crate) enum CoreSchema {
+    Function(FunctionSchema),
+    Variable(VariableSchema),
+    CustomEvent(CustomEventSchema),
+    // The schemas of the Time package, which wait on the Core's scheduler
+    Timer(TimerSchema),
+}
+
+impl CoreSchema {
+    fn build(&self, schema: &mut BuildSchema) {
+        match self {
+            Self::Function(function) => function.build(schema),
+            Self::Variable(variable) => variable.build(schema),
+            Self::CustomEvent(custom_event) => custom_event.build(schema),
+            Self::Timer(timer) => timer.build(schema),
+        }
+    }
+
+    // Runs a node that was entered through an exec input, returning the exec
+    // output to continue from
+    pub async fn execute(
+        &self,
+        executor: &Executor,
+        execution: &mut Execution,
+        node: &Arc<Node>,
+    ) -> Result<Option<&'static str>, ExecutionError> {
+        match self {
+            Self::Function(function) => function.execute(executor, execution, node).await,
+            Self::Variable(variable) => Ok(variable.execute(execution, node)),
+            Self::CustomEvent(custom_event) => Ok(custom_event.execute(execution, node)),
+            Self::Timer(timer) => Ok(timer.execute(execution, node).await),
+        }
+    }
+
+    // Sets the outputs of a node that something pulled data from
+    pub fn evaluate(&self, execution: &mut Execution, node: &Node) {
+        if let Self::Variable(variable) = self {
+            variable.evaluate(execution, node);
+        }
+    }
+}
 
 pub struct NodeSchema {
     pub instances: Mutex<PtrWeakHashSet<Weak<Node>>>,
     inner: NodeSchemaInner,
+    // Set for schemas the Core creates for the project and the Time package
+    pub(crate) core: Option<CoreSchema>,
+    // Keeps the schema's functions valid for as long as any node uses it
+    _library: Option<Arc<Library>>,
 }
 
 impl Deref for NodeSchema {
@@ -20,15 +82,85 @@ impl Deref for NodeSchema {
 }
 
 impl NodeSchema {
+    pub fn new(inner: NodeSchemaInner, library: Option<Arc<Library>>) -> Self {
+        Self {
+            instances: Mutex::new(PtrWeakHashSet::new()),
+            inner,
+            core: None,
+            _library: library,
+        }
+    }
+
+    pub(crate) fn new_core(inner: NodeSchemaInner, core: CoreSchema) -> Self {
+        Self {
+            core: Some(core),
+            ..Self::new(inner, None)
+        }
+    }
+
+    pub(crate) fn function(&self) -> Option<&FunctionSchema> {
+        match &self.core {
+            Some(CoreSchema::Function(function)) => Some(function),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn variable(&self) -> Option<&VariableSchema> {
+        match &self.core {
+            Some(CoreSchema::Variable(variable)) => Some(variable),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn custom_event(&self) -> Option<&CustomEventSchema> {
+        match &self.core {
+            Some(CoreSchema::CustomEvent(custom_event)) => Some(custom_event),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn timer(&self) -> Option<Timer> {
+        match &self.core {
+            Some(CoreSchema::Timer(timer)) => Some(timer.timer),
+            _ => None,
+        }
+    }
+
+    // Whether the schema was created by the Core for the project, rather than
+    // coming from a package
+    pub(crate) fn is_generated(&self) -> bool {
+        matches!(
+            self.core,
+            Some(CoreSchema::Function(_) | CoreSchema::Variable(_) | CoreSchema::CustomEvent(_))
+        )
+    }
+
+    // Whether nodes in the graph can use the schema. A function's input and
+    // output nodes can only be used inside of it, as can graph variables.
+    pub(crate) fn available_in(&self, graph: i32) -> bool {
+        match &self.core {
+            Some(CoreSchema::Function(
+                function @ (FunctionSchema::Input { .. } | FunctionSchema::Output { .. }),
+            )) => function.graph() == graph,
+            Some(CoreSchema::Variable(variable)) => variable.scope.contains(graph),
+            _ => true,
+        }
+    }
+
+    pub(crate) fn is_variable(&self, variable: VariableId, access: VariableAccess) -> bool {
+        matches!(self.variable(), Some(v) if v.variable == variable && v.access == access)
+    }
+
     pub fn build(&self, schema: &mut BuildSchema) {
         use macrograph_package_api::NodeSchemaType::*;
 
-        match ***self {
-            Exec { .. } => {
-                schema.exec_input("");
-                schema.exec_output("");
-            }
-            _ => {}
+        if let Some(core) = &self.core {
+            return core.build(schema);
+        }
+
+        if let Exec { .. } = ***self {
+            schema.exec_input("");
+            schema.exec_output("");
         }
 
         (self.build)(schema);
@@ -37,9 +169,6 @@ impl NodeSchema {
 
 impl From<NodeSchemaInner> for NodeSchema {
     fn from(api_node_schema: NodeSchemaInner) -> Self {
-        Self {
-            instances: Mutex::new(PtrWeakHashSet::new()),
-            inner: NodeSchemaInner::from(api_node_schema),
-        }
+        Self::new(api_node_schema, None)
     }
 }
@@ -0,0 +1,196 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Instant,
+};
+
+use macrograph_package_api::{IOProxy, Value};
+use serde::Serialize;
+use tokio::sync::broadcast;
+use ts_rs::TS;
+
+use crate::{
+    debugger::PausedExecution,
+    execution::{ExecutionError, ExecutionId},
+    node::Node,
+};
+
+#[derive(TS, Serialize, Debug, Clone)]
+#[ts(export)]
+#[serde(tag = "type", content = "data")]
+pub enum TraceEvent {
+    ExecutionStarted {
+        execution: ExecutionId,
+        graph: i32,
+        node: i32,
+    },
+    // input is None for Event and Pure nodes
+    NodeEntered {
+        execution: ExecutionId,
+        graph: i32,
+        node: i32,
+        input: Option<String>,
+    },
+    NodeExited {
+        execution: ExecutionId,
+        graph: i32,
+        node: i32,
+        output: Option<String>,
+        inputs: HashMap<String, Value>,
+        outputs: HashMap<String, Value>,
+        duration_ms: f64,
+    },
+    ExecutionPaused(PausedExecution),
+    ExecutionResumed {
+        execution: ExecutionId,
+    },
+    ExecutionFinished {
+        execution: ExecutionId,
+        graph: i32,
+        node: i32,
+        duration_ms: f64,
+        error: Option<ExecutionError>,
+    },
+}
+
+pub(crate) struct NodeTrace {
+    execution: ExecutionId,
+    graph: i32,
+    node: i32,
+    inputs: HashMap<String, Value>,
+    started: Instant,
+}
+
+// Trace events are only built while something is subscribed and listening,
+// since they clone every value a node reads and writes
+#[derive(Clone)]
+pub struct Tracer {
+    sender: broadcast::Sender<TraceEvent>,
+    subscriptions: Arc<AtomicUsize>,
+}
+
+impl Tracer {
+    pub fn new() -> Self {
+        Self {
+            sender: broadcast::channel(1024).0,
+            subscriptions: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<TraceEvent> {
+        self.sender.subscribe()
+    }
+
+    // Subscriptions are counted, so that one client unsubscribing doesn't
+    // stop tracing for the others
+    pub fn set_subscribed(&self, subscribe: bool) {
+        if subscribe {
+            self.subscriptions.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.subscriptions
+                .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |count| {
+                    count.checked_sub(1)
+                })
+                .ok();
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.subscriptions.load(Ordering::Relaxed) > 0 && self.sender.receiver_count() > 0
+    }
+
+    pub fn emit(&self, event: impl FnOnce() -> TraceEvent) {
+        if self.enabled() {
+            self.sender.send(event()).ok();
+        }
+    }
+
+    pub(crate) fn enter_node(
+        &self,
+        execution: ExecutionId,
+        node: &Node,
+        input: Option<&str>,
+        io: &IOProxy,
+    ) -> Option<NodeTrace> {
+        if !self.enabled() {
+            return None;
+        }
+
+        self.sender
+            .send(TraceEvent::NodeEntered {
+                execution,
+                graph: node.graph_id,
+                node: node.id,
+                input: input.map(|input| input.to_string()),
+            })
+            .ok();
+
+        Some(NodeTrace {
+            execution,
+            graph: node.graph_id,
+            node: node.id,
+            inputs: io.inputs.clone(),
+            started: Instant::now(),
+        })
+    }
+
+    pub(crate) fn exit_node(&self, trace: Option<NodeTrace>, output: Option<&str>, io: &IOProxy) {
+        if let Some(trace) = trace {
+            self.emit(|| TraceEvent::NodeExited {
+                execution: trace.execution,
+                graph: trace.graph,
+                node: trace.node,
+                output: output.map(|output| output.to_string()),
+                inputs: trace.inputs,
+                outputs: io.outputs.clone(),
+                duration_ms: trace.started.elapsed().as_secs_f64() * 1000.0,
+            });
+        }
+    }
+}
+
+impl Default for Tracer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn enabled_until_every_subscription_ends() {
+        let tracer = Tracer::new();
+        let _receiver = tracer.subscribe();
+
+        tracer.set_subscribed(true);
+        tracer.set_subscribed(true);
+        tracer.set_subscribed(false);
+        assert!(tracer.enabled());
+
+        tracer.set_subscribed(false);
+        assert!(!tracer.enabled());
+    }
+
+    #[test]
+    fn unmatched_unsubscribes_are_ignored() {
+        let tracer = Tracer::new();
+        let _receiver = tracer.subscribe();
+
+        tracer.set_subscribed(false);
+        tracer.set_subscribed(true);
+        assert!(tracer.enabled());
+    }
+
+    #[test]
+    fn disabled_without_receivers() {
+        let tracer = Tracer::new();
+
+        tracer.set_subscribed(true);
+        assert!(!tracer.enabled());
+    }
+}
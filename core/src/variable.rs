@@ -0,0 +1,278 @@
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+};
+
+use macrograph_package_api::{
+    primitive::Primitive,
+    schema::{ExecuteFn, NodeSchema as NodeSchemaInner},
+    value::types::ValueType,
+    BuildSchema, Value,
+};
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+use ts_rs::TS;
+
+use crate::{
+    error::CoreError,
+    execution::Execution,
+    function::FUNCTION_PACKAGE,
+    node::Node,
+    schema::{CoreSchema, NodeSchema},
+};
+
+pub type VariableId = u32;
+
+#[derive(TS, Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[ts(export)]
+#[serde(tag = "type", content = "data")]
+pub enum VariableScope {
+    Project,
+    // Only usable by nodes in the graph
+    Graph { graph: i32 },
+}
+
+impl VariableScope {
+    pub fn contains(&self, graph: i32) -> bool {
+        match self {
+            Self::Project => true,
+            Self::Graph { graph: scope } => *scope == graph,
+        }
+    }
+
+    // Whether a graph could use variables from both scopes, in which case
+    // their names have to differ
+    pub fn overlaps(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Graph { graph: a }, Self::Graph { graph: b }) => a == b,
+            _ => true,
+        }
+    }
+}
+
+#[derive(TS, Serialize, Deserialize, Debug, Clone)]
+#[ts(export)]
+pub struct Variable {
+    pub id: VariableId,
+    pub name: String,
+    pub scope: VariableScope,
+    // A variable's type is the type of its initial value
+    pub value: Primitive,
+}
+
+impl Variable {
+    pub fn r#type(&self) -> ValueType {
+        ValueType::Primitive(self.value.r#type())
+    }
+}
+
+#[derive(Default)]
+struct VariablesInner {
+    id_counter: VariableId,
+    variables: BTreeMap<VariableId, Variable>,
+}
+
+// The values of every variable, shared with executors so that Set nodes can
+// change them while the Core keeps processing requests
+#[derive(Clone)]
+pub(crate) struct Variables {
+    inner: Arc<Mutex<VariablesInner>>,
+    // Receives each variable after its value is set
+    changed: UnboundedSender<Variable>,
+}
+
+impl Variables {
+    pub fn new() -> (Self, UnboundedReceiver<Variable>) {
+        let (changed, receiver) = unbounded_channel();
+
+        (
+            Self {
+                inner: Default::default(),
+                changed,
+            },
+            receiver,
+        )
+    }
+
+    pub fn all(&self) -> Vec<Variable> {
+        self.inner
+            .lock()
+            .unwrap()
+            .variables
+            .values()
+            .cloned()
+            .collect()
+    }
+
+    pub fn get(&self, id: VariableId) -> Option<Variable> {
+        self.inner.lock().unwrap().variables.get(&id).cloned()
+    }
+
+    pub fn create(&self, name: String, scope: VariableScope, value: Primitive) -> Variable {
+        let mut inner = self.inner.lock().unwrap();
+
+        let id = inner.id_counter;
+        inner.id_counter += 1;
+
+        let variable = Variable {
+            id,
+            name,
+            scope,
+            value,
+        };
+        inner.variables.insert(id, variable.clone());
+
+        variable
+    }
+
+    // Adds a variable loaded from a project, keeping its ID
+    pub fn insert(&self, variable: Variable) {
+        let mut inner = self.inner.lock().unwrap();
+
+        inner.id_counter = inner.id_counter.max(variable.id + 1);
+        inner.variables.insert(variable.id, variable);
+    }
+
+    pub fn remove(&self, id: VariableId) -> Option<Variable> {
+        self.inner.lock().unwrap().variables.remove(&id)
+    }
+
+    pub fn clear(&self) {
+        *self.inner.lock().unwrap() = VariablesInner::default();
+    }
+
+    pub fn set(&self, id: VariableId, value: Primitive) -> Result<(), CoreError> {
+        let variable = {
+            let mut inner = self.inner.lock().unwrap();
+            let variable = inner
+                .variables
+                .get_mut(&id)
+                .ok_or(CoreError::UnknownVariable { variable: id })?;
+
+            if !Primitive::same_type(&variable.value, &value) {
+                return Err(CoreError::InvalidValue {
+                    expected: variable.r#type(),
+                    found: ValueType::Primitive(value.r#type()),
+                });
+            }
+
+            variable.value = value;
+            variable.clone()
+        };
+
+        // The Core may have been dropped, in which case nobody is listening
+        self.changed.send(variable).ok();
+
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum VariableAccess {
+    Get,
+    Set,
+    // Fires whenever the variable is set
+    Changed,
+}
+
+// Like function schemas, variable schemas are created by the Core and have
+// their IO built from the variable's type
+#[derive(Clone)]
+pub(crate) struct VariableSchema {
+    pub variable: VariableId,
+    pub scope: VariableScope,
+    pub access: VariableAccess,
+    r#type: ValueType,
+    variables: Variables,
+}
+
+impl VariableSchema {
+    pub fn build(&self, schema: &mut BuildSchema) {
+        match self.access {
+            VariableAccess::Get => {
+                schema.data_output("Value", self.r#type);
+            }
+            VariableAccess::Set => {
+                schema.exec_input("");
+                schema.exec_output("");
+                schema.data_input("Value", self.r#type);
+            }
+            VariableAccess::Changed => {
+                schema.exec_output("");
+                schema.data_output("Value", self.r#type);
+            }
+        }
+    }
+
+    // Only Set nodes are executed, since Get is pure and Changed is an event
+    pub fn execute(&self, execution: &Execution, node: &Node) -> Option<&'static str> {
+        let value = node
+            .find_data_input("Value")
+            .map(|input| execution.input_value(node, &input).as_ref().clone());
+
+        if let Some(Value::Primitive(value)) = value {
+            // Fails if the variable was deleted, leaving nothing to set
+            self.variables.set(self.variable, value).ok();
+        }
+
+        Some("")
+    }
+
+    // Nodes of deleted variables keep their last value
+    pub fn evaluate(&self, execution: &mut Execution, node: &Node) {
+        if let (Some(variable), Some(output)) = (
+            self.variables.get(self.variable),
+            node.find_data_output("Value"),
+        ) {
+            execution.set_output_value(node, &output, Arc::new(variable.value.into()));
+        }
+    }
+}
+
+// Creates the Get, Set and Changed schemas for a variable
+pub(crate) fn variable_schemas(variable: &Variable, variables: &Variables) -> [Arc<NodeSchema>; 3] {
+    let schema = |access| VariableSchema {
+        variable: variable.id,
+        scope: variable.scope,
+        access,
+        r#type: variable.r#type(),
+        variables: variables.clone(),
+    };
+
+    // Get and Changed need the right NodeSchemaType to be evaluated lazily
+    // and fired, but the VariableSchema reads and writes the values itself
+    let get = NodeSchemaInner::new_pure(
+        &format!("Get {}", variable.name),
+        |_| {},
+        ExecuteFn::Sync(|_, _| ()),
+    );
+    let set = NodeSchemaInner::new_base(
+        &format!("Set {}", variable.name),
+        |_| {},
+        ExecuteFn::Sync(|_, _| None),
+    );
+    let changed = NodeSchemaInner::new_event(
+        &format!("{} Changed", variable.name),
+        |_| {},
+        |io, data| {
+            if let Some(value) = data.downcast_ref::<Primitive>() {
+                io.outputs.insert("Value".to_string(), value.clone().into());
+            }
+
+            Some("")
+        },
+    );
+
+    [
+        (get, VariableAccess::Get),
+        (set, VariableAccess::Set),
+        (changed, VariableAccess::Changed),
+    ]
+    .map(|(mut inner, access)| {
+        inner.package = FUNCTION_PACKAGE.to_string();
+        Arc::new(NodeSchema::new_core(
+            inner,
+            CoreSchema::Variable(schema(access)),
+        ))
+    })
+}
@@ -34,7 +34,7 @@ pub async fn run(mut ctx: EngineContext) {
                 };
 
                 if let Some(key) = Key::from_rdev(key) {
-                    let event_name = format!("{}", key.to_string().to_uppercase());
+                    let event_name = key.to_string().to_uppercase();
 
                     let key_event = KeyEvent {
                         key,
@@ -58,7 +58,7 @@ pub async fn run(mut ctx: EngineContext) {
                 };
 
                 if let Some(key) = Key::from_rdev(key) {
-                    let event_name = format!("{}", key.to_string().to_uppercase());
+                    let event_name = key.to_string().to_uppercase();
 
                     let key_event = KeyEvent {
                         key,
@@ -6,10 +6,18 @@ mod types;
 use engine::run;
 
 use key_event::KeyEvent;
-use macrograph_package_api::{engine::EngineConfig, fire_fn, package::Package, run_fn};
+use macrograph_package_api::{
+    engine::{block_on_engine, EngineConfig, EngineContext},
+    fire_fn,
+    package::Package,
+    package_descriptor,
+};
 use rdev::{listen, Event};
+use std::{sync::Mutex, thread::JoinHandle};
 use tokio::sync::mpsc;
 
+package_descriptor!();
+
 const PRESSED: &str = "Pressed";
 const RELEASED: &str = "Released";
 const SHIFT: &str = "Shift Pressed";
@@ -17,34 +25,63 @@ const CTRL: &str = "Ctrl Pressed";
 const ALT: &str = "Alt Pressed";
 const META: &str = "Meta Pressed";
 
-#[no_mangle]
-pub fn create_package() -> Package {
-    let mut package = Package::new("Keyboard");
+// rdev can't stop listening, so the listener is started by the engine's first
+// run and kept after that. Each run replaces the channel that it sends events to.
+static EVENT_SENDER: Mutex<Option<mpsc::UnboundedSender<Event>>> = Mutex::new(None);
+static LISTENER: Mutex<Option<JoinHandle<()>>> = Mutex::new(None);
+
+fn event_receiver() -> mpsc::UnboundedReceiver<Event> {
+    let (tx, rx) = mpsc::unbounded_channel::<Event>();
+    *EVENT_SENDER.lock().unwrap() = Some(tx);
+    rx
+}
 
-    package.set_engine({
-        let (tx, rx) = mpsc::unbounded_channel::<Event>();
+fn start_listener() {
+    let mut listener = LISTENER.lock().unwrap();
 
-        let cb = || {
-            listen(move |event| {
-                tx.send(event).unwrap();
+    if listener.is_none() {
+        *listener = Some(std::thread::spawn(|| {
+            listen(|event| {
+                if let Some(tx) = EVENT_SENDER.lock().unwrap().as_ref() {
+                    tx.send(event).ok();
+                }
             })
-            .unwrap()
-        };
-
-        // macos isn't blocking
-        if cfg!(target_os = "macos") {
-            cb();
-        } else {
-            std::thread::spawn(cb);
-        }
+            .unwrap();
+
+            // macos isn't blocking, but keeps calling the callback
+            loop {
+                std::thread::park();
+            }
+        }));
+    }
+}
 
-        EngineConfig {
-            run: run_fn!(run),
-            state: Some(Box::new(rx)),
+// The listener runs code from this library and can't be stopped, so once the
+// engine is asked to stop it waits on the listener instead of returning. The core
+// sees that the engine never finished and keeps the library loaded.
+fn run_engine(ctx: EngineContext) {
+    start_listener();
+    block_on_engine(ctx, run);
+
+    let listener = LISTENER.lock().unwrap().take();
+
+    if let Some(listener) = listener {
+        if let Err(panic) = listener.join() {
+            std::panic::resume_unwind(panic);
         }
+    }
+}
+
+#[no_mangle]
+pub fn create_package() -> Package {
+    let mut package = Package::new("Keyboard");
+
+    package.set_engine(EngineConfig {
+        run: run_engine,
+        state: Some(|| Box::new(event_receiver())),
     });
 
-    for c in 'A'..'Z' {
+    for c in 'A'..='Z' {
         package.add_event_schema(
             &c.to_string(),
             |s| {
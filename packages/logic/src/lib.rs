@@ -1,4 +1,15 @@
-use macrograph_package_api::{exec_fn, package::Package};
+use macrograph_package_api::{
+    exec_fn, list::ListItem, package::Package, package_descriptor,
+    value::types::WildcardConstraint, FlowContext, FlowStep, IOProxy, Value,
+};
+
+package_descriptor!();
+
+// Switch on String has an input and output for as many of these as its Cases
+// property says
+const CASES: [&str; 8] = [
+    "Case 0", "Case 1", "Case 2", "Case 3", "Case 4", "Case 5", "Case 6", "Case 7",
+];
 
 #[no_mangle]
 pub fn create_package() -> Package {
@@ -18,5 +29,285 @@ pub fn create_package() -> Package {
         }),
     );
 
+    pkg.add_pure_schema(
+        "AND",
+        |s| {
+            s.bool_input("One");
+            s.bool_input("Two");
+            s.bool_output("Value");
+        },
+        exec_fn!(|io, _ctx| {
+            let value = io.get_bool("One").unwrap_or(false) && io.get_bool("Two").unwrap_or(false);
+            io.set_bool("Value", value);
+        }),
+    );
+
+    pkg.add_pure_schema(
+        "OR",
+        |s| {
+            s.bool_input("One");
+            s.bool_input("Two");
+            s.bool_output("Value");
+        },
+        exec_fn!(|io, _ctx| {
+            let value = io.get_bool("One").unwrap_or(false) || io.get_bool("Two").unwrap_or(false);
+            io.set_bool("Value", value);
+        }),
+    );
+
+    pkg.add_pure_schema(
+        "NOT",
+        |s| {
+            s.bool_input("Input");
+            s.bool_output("Output");
+        },
+        exec_fn!(|io, _ctx| {
+            let value = !io.get_bool("Input").unwrap_or(false);
+            io.set_bool("Output", value);
+        }),
+    );
+
+    pkg.add_pure_schema(
+        "Equals",
+        |s| {
+            // Both take the type of whichever is connected first
+            s.wildcard_input("One", 0, WildcardConstraint::Primitive);
+            s.wildcard_input("Two", 0, WildcardConstraint::Primitive);
+            s.bool_output("Value");
+        },
+        exec_fn!(|io, _ctx| {
+            let value = match (io.inputs.get("One"), io.inputs.get("Two")) {
+                (Some(Value::Primitive(one)), Some(Value::Primitive(two))) => one == two,
+                _ => false,
+            };
+            io.set_bool("Value", value);
+        }),
+    );
+
+    pkg.add_flow_schema(
+        "Sequence",
+        |s| {
+            s.exec_input("");
+            s.exec_output("Then 0");
+            s.exec_output("Then 1");
+            s.exec_output("Then 2");
+        },
+        |_, ctx| match ctx.iteration {
+            0 => FlowStep::Branch("Then 0"),
+            1 => FlowStep::Branch("Then 1"),
+            _ => FlowStep::Then("Then 2"),
+        },
+    );
+
+    pkg.add_flow_schema(
+        "For Each Int",
+        |s| {
+            s.exec_input("");
+            s.list_input::<i32>("List");
+            s.exec_output("Loop Body");
+            s.int_output("Element");
+            s.int_output("Index");
+            s.exec_output("Completed");
+        },
+        for_each,
+    );
+
+    pkg.add_flow_schema(
+        "For Each Float",
+        |s| {
+            s.exec_input("");
+            s.list_input::<f64>("List");
+            s.exec_output("Loop Body");
+            s.float_output("Element");
+            s.int_output("Index");
+            s.exec_output("Completed");
+        },
+        for_each,
+    );
+
+    pkg.add_flow_schema(
+        "For Each String",
+        |s| {
+            s.exec_input("");
+            s.list_input::<String>("List");
+            s.exec_output("Loop Body");
+            s.string_output("Element");
+            s.int_output("Index");
+            s.exec_output("Completed");
+        },
+        for_each,
+    );
+
+    pkg.add_flow_schema(
+        "For Each Bool",
+        |s| {
+            s.exec_input("");
+            s.list_input::<bool>("List");
+            s.exec_output("Loop Body");
+            s.bool_output("Element");
+            s.int_output("Index");
+            s.exec_output("Completed");
+        },
+        for_each,
+    );
+
+    pkg.add_flow_schema(
+        "While",
+        |s| {
+            s.exec_input("");
+            s.bool_input("Condition");
+            s.exec_output("Loop Body");
+            s.exec_output("Completed");
+        },
+        |io, _| match io.get_bool("Condition").unwrap_or(false) {
+            true => FlowStep::Branch("Loop Body"),
+            false => FlowStep::Then("Completed"),
+        },
+    );
+
+    pkg.add_flow_schema(
+        "Gate",
+        |s| {
+            s.exec_input("Enter");
+            s.exec_input("Open");
+            s.exec_input("Close");
+            s.exec_input("Toggle");
+            s.bool_input("Start Closed");
+            s.exec_output("Exit");
+        },
+        |io, ctx| {
+            let start_closed = io.get_bool("Start Closed").unwrap_or(false);
+            let input = ctx.input;
+            let open = ctx.state::<Option<bool>>().get_or_insert(!start_closed);
+
+            match input {
+                "Enter" if *open => return FlowStep::Then("Exit"),
+                "Open" => *open = true,
+                "Close" => *open = false,
+                "Toggle" => *open = !*open,
+                _ => {}
+            }
+
+            FlowStep::Done
+        },
+    );
+
+    pkg.add_flow_schema(
+        "Do Once",
+        |s| {
+            s.exec_input("");
+            s.exec_input("Reset");
+            s.bool_input("Start Closed");
+            s.exec_output("Completed");
+        },
+        |io, ctx| {
+            let start_closed = io.get_bool("Start Closed").unwrap_or(false);
+            let input = ctx.input;
+            let done = ctx.state::<Option<bool>>().get_or_insert(start_closed);
+
+            match input {
+                "" if !*done => {
+                    *done = true;
+                    FlowStep::Then("Completed")
+                }
+                "Reset" => {
+                    *done = false;
+                    FlowStep::Done
+                }
+                _ => FlowStep::Done,
+            }
+        },
+    );
+
+    pkg.add_flow_schema(
+        "Flip Flop",
+        |s| {
+            s.exec_input("");
+            s.exec_output("A");
+            s.exec_output("B");
+            s.bool_output("Is A");
+        },
+        |io, ctx| {
+            let flipped = ctx.state::<bool>();
+            let is_a = !*flipped;
+            *flipped = is_a;
+
+            io.set_bool("Is A", is_a);
+
+            FlowStep::Then(if is_a { "A" } else { "B" })
+        },
+    );
+
+    pkg.add_base_schema(
+        "Switch on Int",
+        |s| {
+            s.exec_input("");
+            s.int_input("Selection");
+            s.exec_output("0");
+            s.exec_output("1");
+            s.exec_output("2");
+            s.exec_output("3");
+            s.exec_output("Default");
+        },
+        exec_fn!(|io, _ctx| {
+            Some(match io.get_int("Selection") {
+                Some(0) => "0",
+                Some(1) => "1",
+                Some(2) => "2",
+                Some(3) => "3",
+                _ => "Default",
+            })
+        }),
+    );
+
+    pkg.add_base_schema(
+        "Switch on String",
+        |s| {
+            let count = s.int_property("Cases", 3, 1, CASES.len() as i32) as usize;
+
+            s.exec_input("");
+            s.string_input("Selection");
+
+            for case in &CASES[..count] {
+                s.string_input(case);
+            }
+
+            for case in &CASES[..count] {
+                s.exec_output(case);
+            }
+
+            s.exec_output("Default");
+        },
+        exec_fn!(|io, _ctx| {
+            let selection = io.get_string("Selection");
+
+            Some(
+                CASES
+                    .into_iter()
+                    .take_while(|case| io.inputs.contains_key(*case))
+                    .find(|case| io.get_string(case) == selection)
+                    .unwrap_or("Default"),
+            )
+        }),
+    );
+
     pkg
 }
+
+// Runs the loop body once for each element of the list
+fn for_each(io: &mut IOProxy, ctx: &mut FlowContext) -> FlowStep {
+    let element = match io.inputs.get("List") {
+        Some(Value::List(list)) => list.lock().unwrap().get(ctx.iteration).cloned(),
+        _ => None,
+    };
+
+    match element {
+        Some(ListItem::Primitive(element)) => {
+            io.outputs.insert("Element".to_string(), element.into());
+            io.set_int("Index", ctx.iteration as i32);
+
+            FlowStep::Branch("Loop Body")
+        }
+        None => FlowStep::Then("Completed"),
+    }
+}
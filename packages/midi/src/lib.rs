@@ -1,8 +1,10 @@
-use macrograph_package_api::package::Package;
+use macrograph_package_api::{package::Package, package_descriptor};
 
 
 mod engine;
 
+package_descriptor!();
+
 #[no_mangle]
 pub fn create_package() -> Package {
     let mut package = Package::new("MIDI");
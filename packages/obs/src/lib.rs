@@ -4,9 +4,13 @@ pub mod schemas;
 mod types;
 
 use engine::run;
-use macrograph_package_api::{engine::EngineConfig, package::Package, run_fn};
+use macrograph_package_api::{
+    engine::EngineConfig, package::Package, package_descriptor, run_fn,
+};
 use schemas::create_schemas;
 
+package_descriptor!();
+
 #[no_mangle]
 pub fn create_package() -> Package {
     let mut package = Package::new("OBS");
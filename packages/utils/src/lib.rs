@@ -1,4 +1,15 @@
-use macrograph_package_api::{exec_fn, package::Package};
+use macrograph_package_api::{
+    exec_fn,
+    list::{List, ListItem},
+    package::Package,
+    package_descriptor,
+    value::types::{ListType, PrimitiveType, WildcardConstraint},
+    Value,
+};
+
+package_descriptor!();
+
+const MAX_LIST_INPUTS: i32 = 16;
 
 #[no_mangle]
 pub fn create_package() -> Package {
@@ -7,21 +18,54 @@ pub fn create_package() -> Package {
     package.add_exec_schema(
         "Print",
         |s| {
-            s.string_input("Value");
+            s.wildcard_input("Value", 0, WildcardConstraint::Any);
         },
-        exec_fn!(|n, _ctx| {
-            n.get_string("Value").map(|v| println!("Print: {}", v));
+        exec_fn!(|io, _ctx| {
+            match io.inputs.get("Value") {
+                Some(Value::Primitive(v)) => println!("Print: {}", v),
+                Some(Value::List(v)) => println!("Print: {:?}", v.values.lock().unwrap()),
+                None => {}
+            }
         }),
     );
 
-    package.add_exec_schema(
-        "Print List",
-        |io| {
-            io.list_input::<String>("Value");
+    package.add_pure_schema(
+        "Make List",
+        |s| {
+            let r#type = match s
+                .choice_property("Type", &["String", "Int", "Float", "Bool"])
+                .as_str()
+            {
+                "Int" => PrimitiveType::Int,
+                "Float" => PrimitiveType::Float,
+                "Bool" => PrimitiveType::Bool,
+                _ => PrimitiveType::String,
+            };
+            let count = s.int_property("Count", 2, 1, MAX_LIST_INPUTS);
+
+            for index in 0..count {
+                s.data_input(&index.to_string(), r#type.into());
+            }
+
+            s.data_output("List", ListType::Primitive(r#type).into());
         },
         exec_fn!(|io, _ctx| {
-            io.get_list::<String>("Value")
-                .map(|v| println!("Print: {:?}", v.values.lock().unwrap()));
+            // Inputs are numbered from 0 up to the node's Count
+            let items: Vec<_> = (0..)
+                .map_while(|index: i32| match io.inputs.get(&index.to_string()) {
+                    Some(Value::Primitive(item)) => Some(item.clone()),
+                    _ => None,
+                })
+                .collect();
+
+            if let Some(first) = items.first() {
+                let list = List::new(ListType::Primitive(first.r#type()));
+                list.lock()
+                    .unwrap()
+                    .extend(items.into_iter().map(ListItem::from));
+
+                io.outputs.insert("List".to_string(), Value::List(list));
+            }
         }),
     );
 
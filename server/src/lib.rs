@@ -0,0 +1,284 @@
+use std::{
+    net::SocketAddr,
+    path::{Component, Path, PathBuf},
+    sync::Arc,
+};
+
+use futures::{SinkExt, StreamExt};
+use macrograph::{
+    api::{Request, Response},
+    core::CoreController,
+    error::CoreError,
+    notification::CoreNotification,
+    trace::TraceEvent,
+};
+use serde::{Deserialize, Serialize};
+use subtle::ConstantTimeEq;
+use tokio::{
+    net::{TcpListener, TcpStream},
+    sync::{
+        broadcast::{self, error::RecvError},
+        mpsc::{unbounded_channel, UnboundedSender},
+    },
+    task::JoinHandle,
+};
+use tokio_tungstenite::{
+    accept_hdr_async,
+    tungstenite::{
+        handshake::server::{
+            Callback, ErrorResponse, Request as HandshakeRequest, Response as HandshakeResponse,
+        },
+        http::{header::ORIGIN, StatusCode},
+        Message,
+    },
+};
+use ts_rs::TS;
+
+// The id is chosen by the client and echoed back in the matching response,
+// since responses can arrive out of order
+#[derive(TS, Deserialize, Debug)]
+#[ts(export)]
+pub struct ClientMessage {
+    pub id: u32,
+    pub request: Request,
+}
+
+#[derive(TS, Serialize, Debug)]
+#[ts(export)]
+#[serde(tag = "type", content = "data")]
+pub enum ServerMessage {
+    Response { id: u32, response: Response },
+    Notification(CoreNotification),
+    ExecutionTrace(TraceEvent),
+    // Sent for messages that couldn't be parsed, which have no usable id
+    InvalidMessage { message: String },
+}
+
+// Who may connect. Browsers send the Origin of the page that opened the
+// connection, which has to be allowed, so that any page open on the machine
+// can't drive the Core. Every client has to pass the token.
+#[derive(Debug, Clone)]
+pub struct ServerConfig {
+    // Passed in the query string, e.g. ws://127.0.0.1:4000/?token=...
+    pub token: String,
+    pub allowed_origins: Vec<String>,
+    // Where clients can save and load projects, with paths relative to it.
+    // Without one, clients can't save or load projects at all.
+    pub project_dir: Option<PathBuf>,
+}
+
+impl ServerConfig {
+    // Points the paths of project requests into project_dir, refusing ones
+    // that would leave it
+    fn resolve_project_path(&self, request: &mut Request) -> Result<(), CoreError> {
+        let path = match request {
+            Request::SaveProject { path } | Request::LoadProject { path } => path,
+            _ => return Ok(()),
+        };
+
+        match &self.project_dir {
+            Some(dir) if is_relative_descendant(Path::new(path)) => {
+                *path = dir.join(&path).to_string_lossy().into_owned();
+                Ok(())
+            }
+            _ => Err(CoreError::ProjectPathNotAllowed { path: path.clone() }),
+        }
+    }
+}
+
+// Relative paths without any .. stay inside of the directory they're joined to
+fn is_relative_descendant(path: &Path) -> bool {
+    path.components().next().is_some()
+        && path
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+}
+
+// Checks each handshake before accepting it
+impl Callback for &ServerConfig {
+    fn on_request(
+        self,
+        request: &HandshakeRequest,
+        response: HandshakeResponse,
+    ) -> Result<HandshakeResponse, ErrorResponse> {
+        if let Some(origin) = request.headers().get(ORIGIN) {
+            if !self.allowed_origins.iter().any(|allowed| origin == allowed) {
+                return Err(error_response(StatusCode::FORBIDDEN, "Origin not allowed"));
+            }
+        }
+
+        let token = request
+            .uri()
+            .query()
+            .and_then(|query| {
+                form_urlencoded::parse(query.as_bytes())
+                    .find(|(name, _)| name == "token")
+                    .map(|(_, token)| token.into_owned())
+            })
+            .unwrap_or_default();
+
+        // Compared in constant time so that the token can't be guessed
+        // from how long rejecting it takes
+        match bool::from(token.as_bytes().ct_eq(self.token.as_bytes())) {
+            true => Ok(response),
+            false => Err(error_response(StatusCode::UNAUTHORIZED, "Invalid token")),
+        }
+    }
+}
+
+fn error_response(status: StatusCode, message: &str) -> ErrorResponse {
+    let mut response = ErrorResponse::new(Some(message.to_string()));
+    *response.status_mut() = status;
+    response
+}
+
+// Serves the Core API over WebSocket to every client that connects to the listener
+pub async fn serve(
+    listener: TcpListener,
+    controller: CoreController,
+    config: ServerConfig,
+) -> std::io::Result<()> {
+    let controller = Arc::new(controller);
+    let config = Arc::new(config);
+
+    loop {
+        let (stream, address) = listener.accept().await?;
+
+        tokio::spawn(handle_connection(
+            stream,
+            address,
+            controller.clone(),
+            config.clone(),
+        ));
+    }
+}
+
+async fn handle_connection(
+    stream: TcpStream,
+    address: SocketAddr,
+    controller: Arc<CoreController>,
+    config: Arc<ServerConfig>,
+) {
+    let socket = match accept_hdr_async(stream, &*config).await {
+        Ok(socket) => socket,
+        Err(e) => {
+            log::warn!("WebSocket handshake with {} failed: {}", address, e);
+            return;
+        }
+    };
+
+    let (mut sink, mut stream) = socket.split();
+    let (sender, mut receiver) = unbounded_channel();
+
+    forward(
+        controller.subscribe(),
+        sender.clone(),
+        ServerMessage::Notification,
+    );
+    // Each connection holds at most one trace subscription, and only gets
+    // trace events while it does
+    let mut trace: Option<JoinHandle<()>> = None;
+
+    let writer = tokio::spawn(async move {
+        while let Some(message) = receiver.recv().await {
+            let text = match serde_json::to_string(&message) {
+                Ok(text) => text,
+                Err(e) => {
+                    log::error!("Failed to serialize message: {}", e);
+                    continue;
+                }
+            };
+
+            if sink.send(Message::Text(text)).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    while let Some(Ok(message)) = stream.next().await {
+        let text = match message {
+            Message::Text(text) => text,
+            Message::Close(_) => break,
+            _ => continue,
+        };
+
+        match serde_json::from_str::<ClientMessage>(&text) {
+            // Requests are handled concurrently so that a request waiting on
+            // the Core doesn't hold up ones that don't, like CancelExecution
+            Ok(ClientMessage { id, mut request }) => {
+                if let Err(e) = config.resolve_project_path(&mut request) {
+                    let response = Response::Error {
+                        request: request.name().to_string(),
+                        message: e.to_string(),
+                        kind: e,
+                    };
+                    sender.send(ServerMessage::Response { id, response }).ok();
+                    continue;
+                }
+
+                if let Request::SubscribeExecutionTrace { subscribe } = request {
+                    if subscribe == trace.is_some() {
+                        let response = Response::SubscribeExecutionTrace;
+                        sender.send(ServerMessage::Response { id, response }).ok();
+                        continue;
+                    }
+
+                    if subscribe {
+                        trace = Some(forward(
+                            controller.subscribe_execution_trace(),
+                            sender.clone(),
+                            ServerMessage::ExecutionTrace,
+                        ));
+                    } else if let Some(trace) = trace.take() {
+                        trace.abort();
+                    }
+                }
+
+                let controller = controller.clone();
+                let sender = sender.clone();
+
+                tokio::spawn(async move {
+                    let response = controller.send(request).await;
+                    sender.send(ServerMessage::Response { id, response }).ok();
+                });
+            }
+            Err(e) => {
+                sender
+                    .send(ServerMessage::InvalidMessage {
+                        message: e.to_string(),
+                    })
+                    .ok();
+            }
+        }
+    }
+
+    writer.abort();
+
+    if let Some(trace) = trace {
+        trace.abort();
+        controller
+            .send(Request::SubscribeExecutionTrace { subscribe: false })
+            .await;
+    }
+}
+
+// Forwarding stops the next time something is sent after the connection closes
+fn forward<T: Clone + Send + 'static>(
+    mut receiver: broadcast::Receiver<T>,
+    sender: UnboundedSender<ServerMessage>,
+    wrap: fn(T) -> ServerMessage,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        loop {
+            match receiver.recv().await {
+                Ok(item) => {
+                    if sender.send(wrap(item)).is_err() {
+                        break;
+                    }
+                }
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => break,
+            }
+        }
+    })
+}
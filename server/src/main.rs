@@ -0,0 +1,84 @@
+use std::{env, path::PathBuf};
+
+use macrograph::{core::Core, discovery::default_package_dirs};
+use macrograph_server::ServerConfig;
+use rand::{distributions::Alphanumeric, Rng};
+use tokio::net::TcpListener;
+
+const DEFAULT_ADDRESS: &str = "127.0.0.1:4000";
+const TOKEN_LENGTH: usize = 32;
+
+// Usage: macrograph-server [address] [token]
+#[tokio::main]
+async fn main() {
+    simple_logger::init_with_level(log::Level::Info).unwrap();
+
+    let mut args = env::args().skip(1);
+
+    let address = args
+        .next()
+        .or_else(|| env::var("MACROGRAPH_SERVER_ADDRESS").ok())
+        .unwrap_or_else(|| DEFAULT_ADDRESS.to_string());
+    // A random token is generated if none is given
+    let token = args
+        .next()
+        .or_else(|| env::var("MACROGRAPH_SERVER_TOKEN").ok())
+        .unwrap_or_else(|| {
+            rand::thread_rng()
+                .sample_iter(&Alphanumeric)
+                .take(TOKEN_LENGTH)
+                .map(char::from)
+                .collect()
+        });
+    // Comma separated, e.g. http://localhost:3000,https://example.com
+    let allowed_origins = env::var("MACROGRAPH_SERVER_ORIGINS")
+        .map(|origins| {
+            origins
+                .split(',')
+                .map(|origin| origin.trim().to_string())
+                .filter(|origin| !origin.is_empty())
+                .collect()
+        })
+        .unwrap_or_default();
+
+    // Projects can only be saved and loaded over the server inside of this directory
+    let project_dir = env::var_os("MACROGRAPH_SERVER_PROJECT_DIR").map(PathBuf::from);
+
+    let mut core = Core::new();
+
+    for failure in core.load_package_dirs(&default_package_dirs()) {
+        log::warn!(
+            "Failed to load package at {}: {}",
+            failure.path.display(),
+            failure.error
+        );
+    }
+
+    core.setup();
+
+    let controller = core.get_controller();
+
+    tokio::spawn(async move {
+        core.start().await;
+    });
+
+    let listener = TcpListener::bind(&address)
+        .await
+        .unwrap_or_else(|e| panic!("Failed to listen on {}: {}", address, e));
+
+    println!(
+        "Listening on ws://{}/?token={}",
+        listener.local_addr().unwrap(),
+        token
+    );
+
+    let config = ServerConfig {
+        token,
+        allowed_origins,
+        project_dir,
+    };
+
+    if let Err(e) = macrograph_server::serve(listener, controller, config).await {
+        log::error!("Server stopped: {}", e);
+    }
+}
@@ -0,0 +1,225 @@
+use std::{net::SocketAddr, path::PathBuf, time::Duration};
+
+use futures::{SinkExt, StreamExt};
+use macrograph::core::Core;
+use macrograph_server::{serve, ServerConfig};
+use serde_json::{json, Value};
+use tokio::net::{TcpListener, TcpStream};
+use tokio_tungstenite::{
+    connect_async,
+    tungstenite::{client::IntoClientRequest, http::header::ORIGIN, Message},
+    MaybeTlsStream, WebSocketStream,
+};
+
+const TOKEN: &str = "test-token";
+const ALLOWED_ORIGIN: &str = "http://localhost:3000";
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+type Socket = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+async fn start_server() -> SocketAddr {
+    start_server_in(None).await
+}
+
+async fn start_server_in(project_dir: Option<PathBuf>) -> SocketAddr {
+    let mut core = Core::new();
+    let controller = core.get_controller();
+
+    tokio::spawn(async move {
+        core.start().await;
+    });
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+
+    let config = ServerConfig {
+        token: TOKEN.to_string(),
+        allowed_origins: vec![ALLOWED_ORIGIN.to_string()],
+        project_dir,
+    };
+
+    tokio::spawn(serve(listener, controller, config));
+
+    address
+}
+
+async fn connect(address: SocketAddr, token: &str, origin: Option<&str>) -> Option<Socket> {
+    let mut request = format!("ws://{}/?token={}", address, token)
+        .into_client_request()
+        .unwrap();
+
+    if let Some(origin) = origin {
+        request
+            .headers_mut()
+            .insert(ORIGIN, origin.parse().unwrap());
+    }
+
+    connect_async(request).await.ok().map(|(socket, _)| socket)
+}
+
+async fn send(socket: &mut Socket, message: Value) {
+    socket
+        .send(Message::Text(message.to_string()))
+        .await
+        .unwrap();
+}
+
+// Skips messages until one of the type arrives
+async fn receive(socket: &mut Socket, r#type: &str) -> Value {
+    let receive = async {
+        while let Some(message) = socket.next().await {
+            if let Message::Text(text) = message.unwrap() {
+                let message: Value = serde_json::from_str(&text).unwrap();
+
+                if message["type"] == r#type {
+                    return message;
+                }
+            }
+        }
+
+        panic!("Connection closed before receiving {}", r#type);
+    };
+
+    tokio::time::timeout(TIMEOUT, receive)
+        .await
+        .unwrap_or_else(|_| panic!("Timed out waiting for {}", r#type))
+}
+
+#[tokio::test]
+async fn response_echoes_request_id() {
+    let address = start_server().await;
+    let mut socket = connect(address, TOKEN, None).await.unwrap();
+
+    send(
+        &mut socket,
+        json!({ "id": 7, "request": { "type": "GetProject" } }),
+    )
+    .await;
+
+    let message = receive(&mut socket, "Response").await;
+
+    assert_eq!(message["data"]["id"], 7);
+    assert_eq!(message["data"]["response"]["type"], "GetProject");
+}
+
+#[tokio::test]
+async fn invalid_message_on_bad_json() {
+    let address = start_server().await;
+    let mut socket = connect(address, TOKEN, None).await.unwrap();
+
+    socket
+        .send(Message::Text("not json".to_string()))
+        .await
+        .unwrap();
+
+    let message = receive(&mut socket, "InvalidMessage").await;
+
+    assert!(message["data"]["message"].is_string());
+}
+
+#[tokio::test]
+async fn notification_after_graph_mutation() {
+    let address = start_server().await;
+    let mut socket = connect(address, TOKEN, None).await.unwrap();
+
+    send(
+        &mut socket,
+        json!({ "id": 1, "request": { "type": "CreateGraph" } }),
+    )
+    .await;
+
+    let message = receive(&mut socket, "Notification").await;
+
+    assert_eq!(message["data"]["type"], "GraphCreated");
+    assert_eq!(message["data"]["data"]["name"], "Graph 2");
+}
+
+#[tokio::test]
+async fn rejects_invalid_token() {
+    let address = start_server().await;
+
+    assert!(connect(address, "wrong", None).await.is_none());
+}
+
+#[tokio::test]
+async fn rejects_origins_not_allowed() {
+    let address = start_server().await;
+
+    assert!(connect(address, TOKEN, Some("http://evil.example"))
+        .await
+        .is_none());
+    assert!(connect(address, TOKEN, Some(ALLOWED_ORIGIN))
+        .await
+        .is_some());
+}
+
+#[tokio::test]
+async fn accepts_percent_encoded_token() {
+    let address = start_server().await;
+
+    assert!(connect(address, "test%2Dtoken", None).await.is_some());
+}
+
+async fn save_project(socket: &mut Socket, path: &str) -> Value {
+    send(
+        socket,
+        json!({ "id": 1, "request": { "type": "SaveProject", "data": { "path": path } } }),
+    )
+    .await;
+
+    receive(socket, "Response").await["data"]["response"].clone()
+}
+
+#[tokio::test]
+async fn refuses_projects_without_project_dir() {
+    let address = start_server().await;
+    let mut socket = connect(address, TOKEN, None).await.unwrap();
+
+    let response = save_project(&mut socket, "project.json").await;
+
+    assert_eq!(response["type"], "Error");
+    assert_eq!(response["data"]["kind"]["type"], "ProjectPathNotAllowed");
+}
+
+#[tokio::test]
+async fn keeps_projects_inside_project_dir() {
+    let dir = std::env::temp_dir().join(format!("macrograph-server-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let address = start_server_in(Some(dir.clone())).await;
+    let mut socket = connect(address, TOKEN, None).await.unwrap();
+
+    let response = save_project(&mut socket, "../project.json").await;
+    assert_eq!(response["type"], "Error");
+
+    let response = save_project(&mut socket, "project.json").await;
+    assert_eq!(response["type"], "SaveProject");
+    assert!(dir.join("project.json").exists());
+
+    std::fs::remove_dir_all(dir).ok();
+}
+
+#[tokio::test]
+async fn repeated_trace_subscriptions_are_answered() {
+    let address = start_server().await;
+    let mut socket = connect(address, TOKEN, None).await.unwrap();
+
+    for id in 0..2 {
+        send(
+            &mut socket,
+            json!({
+                "id": id,
+                "request": { "type": "SubscribeExecutionTrace", "data": { "subscribe": true } }
+            }),
+        )
+        .await;
+
+        let message = receive(&mut socket, "Response").await;
+
+        assert_eq!(message["data"]["id"], id);
+        assert_eq!(
+            message["data"]["response"]["type"],
+            "SubscribeExecutionTrace"
+        );
+    }
+}